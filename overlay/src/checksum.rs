@@ -0,0 +1,77 @@
+//! Checksum algorithms for `#[overlay(checksum = ...)]` fields. Each computes the value to store
+//! in the field from the covered bytes, with the field's own bits zeroed.
+
+/// The Internet checksum of RFC 1071, as used by IPv4, ICMP, UDP and TCP: the ones' complement
+/// of the ones' complement sum of the big-endian 16-bit words, with an odd final byte padded
+/// with zero.
+pub fn internet(bytes: &[u8]) -> u16 {
+    let mut sum = 0_u32;
+    for word in bytes.chunks(2) {
+        let high = word[0] as u32;
+        let low = word.get(1).copied().unwrap_or(0) as u32;
+        sum += (high << 8) | low;
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
+
+/// CRC-8/SMBUS: polynomial 0x07, initial value 0, no reflection or final XOR
+pub fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0_u8;
+    for &byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// CRC-16/IBM-3740 (also known as CRC-16/CCITT-FALSE): polynomial 0x1021, initial value 0xffff,
+/// no reflection or final XOR
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xffff_u16;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// CRC-32/ISO-HDLC, as used by Ethernet, zlib and PNG: reflected polynomial 0xedb88320, initial
+/// value and final XOR 0xffffffff
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+/// The byte that makes all of the bytes sum to zero (mod 256), as used by SMBIOS and ACPI tables
+pub fn sum8(bytes: &[u8]) -> u8 {
+    let sum = bytes.iter().fold(0_u8, |sum, &byte| sum.wrapping_add(byte));
+
+    sum.wrapping_neg()
+}
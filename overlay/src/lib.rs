@@ -1,11 +1,97 @@
 #![no_std]
 
+pub mod checksum;
+
 pub trait Overlay {
+    /// The leading bytes of a new value, e.g. with its magic fields set; the rest are zero
+    const INIT: &'static [u8] = &[];
+
     fn overlay(bytes: &[u8]) -> Result<&Self, Error>;
     fn overlay_mut(bytes: &mut [u8]) -> Result<&mut Self, Error>;
+
+    /// Checks the contents of the bytes, e.g. that magic fields hold their expected values
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Like `overlay`, but also checks the bytes with `validate`, to recognise a format
+    fn overlay_checked(bytes: &[u8]) -> Result<&Self, Error> {
+        let this = Self::overlay(bytes)?;
+        this.validate()?;
+        Ok(this)
+    }
+
+    /// Like `overlay_mut`, but also checks the bytes with `validate`
+    fn overlay_checked_mut(bytes: &mut [u8]) -> Result<&mut Self, Error> {
+        let this = Self::overlay_mut(bytes)?;
+        this.validate()?;
+        Ok(this)
+    }
 }
 
+/// Variants may be added in minor releases, as the macro gains new checks
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
     InsufficientLength,
+    /// The tag of a tagged union didn't match any of its variants
+    UnknownTag,
+    /// A magic field didn't hold its expected value
+    BadMagic {
+        field: &'static str,
+    },
+    /// A reserved field wasn't zero
+    ReservedNotZero {
+        field: &'static str,
+    },
+}
+
+/// Types that can be overlaid on bytes in place, as the generic fields of an `#[overlay]` struct
+/// are. Implemented by `#[overlay]` structs, and here by bytes and byte arrays.
+///
+/// # Safety
+///
+/// Any `size_of::<Self>()` bytes must be a valid `Self`, and `Self` must have an alignment of 1.
+pub unsafe trait FromAnyBytes {}
+
+// SAFETY: every bit pattern is a valid `u8`, which has an alignment of 1
+unsafe impl FromAnyBytes for u8 {}
+
+// SAFETY: as for `u8`, and arrays have no padding
+unsafe impl<const N: usize> FromAnyBytes for [u8; N] {}
+
+impl Overlay for u8 {
+    fn overlay(bytes: &[u8]) -> Result<&Self, Error> {
+        bytes.first().ok_or(Error::InsufficientLength)
+    }
+
+    fn overlay_mut(bytes: &mut [u8]) -> Result<&mut Self, Error> {
+        bytes.first_mut().ok_or(Error::InsufficientLength)
+    }
+}
+
+impl<const N: usize> Overlay for [u8; N] {
+    fn overlay(bytes: &[u8]) -> Result<&Self, Error> {
+        bytes
+            .get(..N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(Error::InsufficientLength)
+    }
+
+    fn overlay_mut(bytes: &mut [u8]) -> Result<&mut Self, Error> {
+        bytes
+            .get_mut(..N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(Error::InsufficientLength)
+    }
+}
+
+/// Implemented by `#[overlay_enum]`, so fields can check at compile time that every value of the
+/// enum fits in their bits, and store variants that carry data
+pub trait OverlayEnum {
+    /// The largest raw value of any variant
+    const MAX_RAW: u128;
+
+    /// The raw value of the variant
+    fn to_raw(self) -> u128;
 }
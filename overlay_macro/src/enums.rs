@@ -0,0 +1,295 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Ident, Lit, LitInt, Token, Type,
+};
+
+use crate::combine_errors;
+
+/// The widths conversions are generated for, in bits
+const WIDTHS: [u32; 5] = [8, 16, 32, 64, 128];
+
+enum VariantAttribute {
+    /// `range = 0x80..=0xff`: every raw value in the range, carried by the variant
+    Range(u128, u128),
+    /// `other`: any raw value that nothing else matches
+    Other,
+}
+
+enum Value {
+    Single(u128),
+    Range(u128, u128, Type),
+    Other(Type),
+}
+
+/// Generates `TryFrom` and `From` conversions between an enum and each of `u8`..`u128`, plus an
+/// `overlay::OverlayEnum` impl so fields can check that every value fits their bit range. With
+/// errors, the enum is still emitted, without its conversions.
+pub(crate) fn conversions(args: TokenStream, item: DeriveInput) -> TokenStream {
+    let mut item = item;
+    let impls = if args.is_empty() {
+        impls(&mut item)
+    } else {
+        Err(syn::Error::new_spanned(
+            args,
+            "overlay_enum takes no arguments",
+        ))
+    };
+
+    let impls = impls.unwrap_or_else(|error| error.to_compile_error());
+    quote! {
+        #item
+        #impls
+    }
+}
+
+/// Removes the variants' `#[overlay_enum(...)]` attributes, then generates the conversions
+fn impls(item: &mut DeriveInput) -> syn::Result<TokenStream> {
+    let name = item.ident.clone();
+    let data = match &mut item.data {
+        Data::Enum(data) => data,
+        _ => bail!(&name, "overlay_enum can only be used on enums"),
+    };
+
+    let carries_data = data
+        .variants
+        .iter()
+        .any(|variant| !matches!(variant.fields, Fields::Unit));
+
+    let mut values = vec![];
+    let mut next: u128 = 0;
+    // reported together, once every variant has been seen
+    let mut errors = vec![];
+    for variant in &mut data.variants {
+        let variant_name = variant.ident.clone();
+
+        let (ours, attrs) = variant
+            .attrs
+            .drain(..)
+            .partition::<Vec<_>, _>(|attr| attr.path.is_ident("overlay_enum"));
+        variant.attrs = attrs;
+
+        let discriminant = variant.discriminant.clone();
+        // `as` can't be used on an enum with data, and rustc requires a `repr` for
+        // discriminants on such enums, so they're only needed by the conversions
+        if carries_data {
+            variant.discriminant = None;
+        }
+
+        match value(&variant_name, &ours, discriminant, &variant.fields, next) {
+            Ok(value) => {
+                if let Value::Single(value) = value {
+                    next = value.wrapping_add(1);
+                }
+                values.push((variant_name, value));
+            }
+            Err(error) => errors.push(error),
+        }
+    }
+    combine_errors(errors)?;
+
+    // the conversions have to agree in both directions
+    if values
+        .iter()
+        .filter(|(_, value)| matches!(value, Value::Other(_)))
+        .count()
+        > 1
+    {
+        let (name, _) = values
+            .iter()
+            .filter(|(_, value)| matches!(value, Value::Other(_)))
+            .nth(1)
+            .unwrap();
+        bail!(name, "only one variant can be #[overlay_enum(other)]");
+    }
+    let bounds = |value: &Value| match *value {
+        Value::Single(value) => Some((value, value)),
+        Value::Range(start, end, _) => Some((start, end)),
+        Value::Other(_) => None,
+    };
+    for (i, (a_name, a)) in values.iter().enumerate() {
+        for (b_name, b) in &values[i + 1..] {
+            if let (Some(a), Some(b)) = (bounds(a), bounds(b)) {
+                if a.0 <= b.1 && b.0 <= a.1 {
+                    bail!(b_name, "the values of {a_name} and {b_name} overlap");
+                }
+            }
+        }
+    }
+    let max_raw = values
+        .iter()
+        .filter_map(|(_, value)| bounds(value))
+        .map(|(_, end)| end)
+        .max();
+    let max_raw = Literal::u128_unsuffixed(max_raw.unwrap_or(0));
+
+    let mut impls = vec![];
+    for width in WIDTHS {
+        let raw = Ident::new(&format!("u{width}"), proc_macro2::Span::call_site());
+        let max = !0_u128 >> (128 - width);
+
+        let mut try_arms = vec![];
+        let mut from_arms = vec![];
+        let mut fits = true;
+        let mut fallback = quote! { _ => Err(raw) };
+        for (variant_name, value) in &values {
+            match value {
+                Value::Single(value) => {
+                    if *value <= max {
+                        let value = Literal::u128_unsuffixed(*value);
+                        try_arms.push(quote! { #value => Ok(Self::#variant_name) });
+                        from_arms.push(quote! { #name::#variant_name => #value });
+                    } else {
+                        fits = false;
+                    }
+                }
+                Value::Range(start, end, ty) => {
+                    if *start <= max {
+                        let start = Literal::u128_unsuffixed(*start);
+                        let end = Literal::u128_unsuffixed((*end).min(max));
+                        try_arms
+                            .push(quote! { #start..=#end => Ok(Self::#variant_name(raw as #ty)) });
+                    }
+                    fits &= *end <= max;
+                    from_arms.push(quote! { #name::#variant_name(raw) => raw as #raw });
+                }
+                Value::Other(ty) => {
+                    fallback = quote! { _ => Ok(Self::#variant_name(raw as #ty)) };
+                    from_arms.push(quote! { #name::#variant_name(raw) => raw as #raw });
+                }
+            }
+        }
+
+        impls.push(quote! {
+            impl core::convert::TryFrom<#raw> for #name {
+                type Error = #raw;
+
+                #[allow(unreachable_patterns)]
+                fn try_from(raw: #raw) -> core::result::Result<Self, #raw> {
+                    match raw {
+                        #(#try_arms,)*
+                        #fallback,
+                    }
+                }
+            }
+        });
+
+        // a narrower integer can't hold every value, so leave it to the compiler to reject
+        if fits {
+            impls.push(quote! {
+                impl core::convert::From<#name> for #raw {
+                    fn from(val: #name) -> #raw {
+                        match val {
+                            #(#from_arms,)*
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    Ok(quote! {
+        #(#impls)*
+
+        impl overlay::OverlayEnum for #name {
+            const MAX_RAW: u128 = #max_raw;
+
+            fn to_raw(self) -> u128 {
+                u128::from(self)
+            }
+        }
+    })
+}
+
+/// The raw value(s) of a variant, from its `#[overlay_enum(...)]` attributes or discriminant.
+/// `next` is the value an implicit discriminant takes.
+fn value(
+    variant_name: &Ident,
+    attrs: &[Attribute],
+    discriminant: Option<(Token![=], Expr)>,
+    fields: &Fields,
+    next: u128,
+) -> syn::Result<Value> {
+    let mut parsed = None;
+    for attr in attrs {
+        if parsed
+            .replace(attr.parse_args::<VariantAttribute>()?)
+            .is_some()
+        {
+            bail!(
+                attr,
+                "duplicate #[overlay_enum(...)] attribute for {variant_name}"
+            );
+        }
+    }
+
+    Ok(match (parsed, fields) {
+        (None, Fields::Unit) => Value::Single(match discriminant {
+            None => next,
+            Some((_, Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }))) => lit.base10_parse()?,
+            Some((_, expr)) => bail!(
+                expr,
+                "the discriminant of {variant_name} must be a non-negative integer literal"
+            ),
+        }),
+        (Some(parsed), Fields::Unnamed(fields)) if fields.unnamed.len() == 1 => {
+            let ty = fields.unnamed[0].ty.clone();
+            match parsed {
+                VariantAttribute::Range(start, end) => Value::Range(start, end, ty),
+                VariantAttribute::Other => Value::Other(ty),
+            }
+        }
+        (Some(_), fields) => bail!(
+            fields,
+            "{variant_name} must carry its raw value in a single unnamed field"
+        ),
+        (None, _) => bail!(
+            variant_name,
+            "{variant_name} carries data, so needs #[overlay_enum(range = ..)] or #[overlay_enum(other)]"
+        ),
+    })
+}
+
+impl Parse for VariantAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword = input.parse::<Ident>()?;
+        let attr = if keyword == "range" {
+            input.parse::<Token![=]>()?;
+            let start = input.parse::<LitInt>()?.base10_parse()?;
+            let inclusive = input.peek(Token![..=]);
+            if inclusive {
+                input.parse::<Token![..=]>()?;
+            } else {
+                input.parse::<Token![..]>()?;
+            }
+            let end_lit = input.parse::<LitInt>()?;
+            let end = end_lit.base10_parse::<u128>()?;
+            let end = match (inclusive, end.checked_sub(1)) {
+                (true, _) => end,
+                (false, Some(end)) => end,
+                (false, None) => bail!(end_lit, "empty range"),
+            };
+            if start > end {
+                bail!(end_lit, "empty range");
+            }
+
+            VariantAttribute::Range(start, end)
+        } else if keyword == "other" {
+            VariantAttribute::Other
+        } else {
+            bail!(
+                &keyword,
+                "invalid specifier {keyword}, expected `range` or `other`"
+            );
+        };
+
+        // permit a trailing comma
+        let _ = input.parse::<Token![,]>();
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens"));
+        }
+
+        Ok(attr)
+    }
+}
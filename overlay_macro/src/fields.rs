@@ -0,0 +1,1469 @@
+use std::collections::BTreeMap;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Ident, Type, Visibility};
+
+use crate::{
+    array_len, auto_layout, covered_bits, enum_fits_check, enum_repr, enum_to_raw, int_bits,
+    int_width_check, is_u8, kind_type, match_type, mentions_any, packed_start_bit, BitOrder,
+    Checksum, Endian, FieldTy, Kind, Location, OverlayAttribute, SingleOrRange, StructAttribute,
+    MAX_BYTES,
+};
+
+/// The struct's layout so far, built up as each field is expanded in turn
+#[derive(Default)]
+pub(crate) struct Layout {
+    /// Where the next auto-layout field starts, in bits from the start of the struct
+    cursor: u32,
+    /// The end of each fixed field, checked against the struct's size
+    pub(crate) extents: Vec<(Ident, usize)>,
+    /// `(field, alias, bits)` of each fixed field, checked for overlaps
+    pub(crate) coverage: Vec<(Ident, bool, BTreeMap<usize, u8>)>,
+    pub(crate) tail: bool,
+    /// Sizes of the length-driven fields, which follow the fixed fields in declaration order
+    pub(crate) dynamic_sizes: Vec<TokenStream>,
+    /// Types of the fields sized by the struct's parameters, which follow the fixed bytes
+    pub(crate) generic_tys: Vec<Type>,
+}
+
+/// What a field adds to the struct
+#[derive(Default)]
+pub(crate) struct FieldOutput {
+    /// The getter, and any other methods that come before the setters
+    pub(crate) getter: TokenStream,
+    /// Empty for fields modified through a `_mut()` accessor, which is part of `getter`
+    pub(crate) setter: TokenStream,
+    /// Each generated method, checked for collisions
+    pub(crate) accessors: Vec<Ident>,
+    /// How `Debug` shows the field, if it has a value
+    pub(crate) debug_value: Option<TokenStream>,
+    /// Items alongside the struct, e.g. compile-time checks
+    pub(crate) items: Vec<TokenStream>,
+    /// `(index, value)` of each non-zero byte of a magic field
+    pub(crate) magic_bytes: Vec<(usize, u8)>,
+    /// Run to build `Overlay::INIT`, copying in the initial bytes of nested structs
+    pub(crate) init: Vec<TokenStream>,
+    /// Run by `validate()`
+    pub(crate) checks: Vec<TokenStream>,
+    /// Run by `check_reserved()` and `clear_reserved()`
+    pub(crate) reserved: Option<(TokenStream, TokenStream)>,
+    /// The field a len field takes its length from
+    pub(crate) len_field: Option<Ident>,
+}
+
+/// A field being expanded, with its parsed attribute and the names of its accessors
+struct Field<'a> {
+    name: &'a Ident,
+    ty: &'a Type,
+    vis: &'a Visibility,
+    attr: &'a Attribute,
+    ranges: OverlayAttribute,
+    getter_name: Ident,
+    /// `set_x()`, for values
+    setter_name: Ident,
+    /// `x_mut()`, for references
+    mut_name: Ident,
+    bit_order: Option<BitOrder>,
+    endian: Endian,
+    warn_wide_types: bool,
+}
+
+impl Field<'_> {
+    /// Every field has a byte range once laid out, other than tail, len and generic fields
+    fn byte_range(&self) -> &SingleOrRange {
+        self.ranges
+            .byte
+            .as_ref()
+            .expect("fixed fields have a byte range")
+    }
+
+    /// How `Debug` shows a field through its getter
+    fn debug_getter(&self) -> TokenStream {
+        let getter_name = &self.getter_name;
+        quote! { &self.#getter_name() }
+    }
+}
+
+/// For the accessors named after the field
+fn setter_attr() -> TokenStream {
+    // e.g. `_x: u8` -> `set__x()`
+    //                       ^ rustc warns about this
+    quote! { #[allow(non_snake_case)] }
+}
+
+/// Expands `field` from its `#[overlay(...)]` attribute `attr`, placing it after the fields
+/// already in `layout`
+pub(crate) fn expand_field(
+    field: &syn::Field,
+    attr: &Attribute,
+    struct_attrs: &StructAttribute,
+    params: &[Ident],
+    getter_names: &BTreeMap<Ident, Ident>,
+    layout: &mut Layout,
+) -> syn::Result<FieldOutput> {
+    let name = field.ident.as_ref().expect("named field");
+    let mut ranges: OverlayAttribute = attr.parse_args()?;
+    ranges.renumber_bits(struct_attrs.bit_order);
+
+    let bit_order = ranges.bit_order.or(struct_attrs.bit_order);
+    let default_endian = struct_attrs.endian.unwrap_or(Endian::Big);
+    let layout_doc = place(
+        name,
+        attr,
+        &mut ranges,
+        bit_order,
+        default_endian,
+        &mut layout.cursor,
+    )?;
+
+    let ty = &field.ty;
+    if mentions_any(quote! { #ty }, params) != ranges.generic {
+        bail!(
+            ty,
+            "{name}: fields whose type uses the struct's parameters must be \
+             #[overlay(generic)], and only they can be"
+        );
+    }
+
+    let getter_name = ranges.getter_name(name, struct_attrs.getter_prefix.as_ref());
+    let setter_name = ranges
+        .setter
+        .clone()
+        .unwrap_or_else(|| format_ident!("set_{}", name));
+    let mut_name = ranges
+        .setter
+        .clone()
+        .unwrap_or_else(|| format_ident!("{}_mut", name));
+    let field = Field {
+        name,
+        ty,
+        vis: &field.vis,
+        attr,
+        getter_name,
+        setter_name,
+        mut_name,
+        bit_order,
+        endian: ranges.endian.unwrap_or(default_endian),
+        warn_wide_types: struct_attrs.warn_wide_types,
+        ranges,
+    };
+
+    let mut output = if field.ranges.generic {
+        expand_generic(&field, layout)
+    } else if field.ranges.tail {
+        expand_tail(&field, layout)?
+    } else if let Some(len_field) = &field.ranges.len {
+        expand_len(&field, len_field, getter_names, layout)?
+    } else {
+        expand_fixed(&field, layout)?
+    };
+
+    if !field.ranges.reserved {
+        output.accessors.insert(0, field.getter_name.clone());
+    }
+    if !output.getter.is_empty() {
+        let getter = output.getter;
+        output.getter = quote! {
+            #layout_doc
+            #getter
+        };
+    }
+    // magic fields only change through `as_bytes_mut()`
+    if field.ranges.magic.is_some() {
+        output.setter = quote! {};
+    } else if !output.setter.is_empty() {
+        output.accessors.push(field.setter_name.clone());
+    }
+
+    Ok(output)
+}
+
+/// Gives an auto-layout or `bit_offset` field its byte and bit ranges, and moves `cursor` past
+/// the field. Returns a doc comment for the getter, saying where the field was placed.
+fn place(
+    name: &Ident,
+    attr: &Attribute,
+    ranges: &mut OverlayAttribute,
+    bit_order: Option<BitOrder>,
+    default_endian: Endian,
+    cursor: &mut u32,
+) -> syn::Result<TokenStream> {
+    // auto-layout fields follow on from the previous field, while `bit_offset`
+    // fields give their start
+    let (start, width, endian) = match (ranges.width, ranges.bit_offset) {
+        (Some(width), _) => (*cursor, width, ranges.endian.unwrap_or(default_endian)),
+        (None, Some(bit_offset)) => {
+            // bytes are assembled in the same order the bits are numbered
+            let endian = match bit_order {
+                Some(BitOrder::Msb0) => Endian::Big,
+                _ => Endian::Little,
+            };
+            if ranges.endian.is_some_and(|other| other != endian) {
+                bail!(
+                    attr,
+                    "bit_offset fields are little-endian with lsb0, and big-endian with msb0"
+                );
+            }
+
+            (bit_offset, ranges.bit_len.unwrap(), endian)
+        }
+        _ => {
+            if let Some(byte) = &ranges.byte {
+                *cursor = (byte.end_inclusive() + 1) * 8;
+            }
+            return Ok(quote! {});
+        }
+    };
+
+    let end = start.checked_add(width).filter(|&end| end <= MAX_BYTES * 8);
+    let Some(end) = end else {
+        bail!(attr, "fields must end before byte {MAX_BYTES}");
+    };
+
+    let (byte, bits) = auto_layout(start, width, endian);
+    // unaligned fields are integers, assembled from 16 bytes and the top of a 17th at most
+    if bits.is_some() && width > 128 {
+        bail!(
+            attr,
+            "`{name}` isn't byte-aligned, so can be at most 128 bits wide, not {width}"
+        );
+    }
+
+    let doc = match &bits {
+        None => format!(
+            " Laid out at bytes {}..={}",
+            byte.start(),
+            byte.end_inclusive()
+        ),
+        Some(bits) => {
+            let (mut start_bit, mut end_bit) = (bits.start(), bits.end_inclusive());
+            if bit_order == Some(BitOrder::Msb0) {
+                let lim = byte.len() * 8 - 1;
+                (start_bit, end_bit) = (lim - end_bit, lim - start_bit);
+            }
+
+            format!(
+                " Laid out at bytes {}..={}, bits {start_bit}..={end_bit}",
+                byte.start(),
+                byte.end_inclusive(),
+            )
+        }
+    };
+
+    ranges.byte = Some(byte);
+    ranges.bits = bits;
+    ranges.endian = Some(endian);
+    *cursor = end;
+
+    Ok(quote! { #[doc = #doc] })
+}
+
+/// A field sized by the struct's parameters, following the fixed bytes
+fn expand_generic(field: &Field, layout: &mut Layout) -> FieldOutput {
+    let (ty, vis) = (field.ty, field.vis);
+    let (getter_name, mut_name) = (&field.getter_name, &field.mut_name);
+    let setter_attr = setter_attr();
+
+    // the fixed bytes are `self.0`, followed by each generic field in turn
+    let index = syn::Index::from(layout.generic_tys.len() + 1);
+    layout.generic_tys.push(ty.clone());
+
+    FieldOutput {
+        getter: quote! {
+            #vis fn #getter_name(&self) -> &#ty {
+                &self.#index
+            }
+
+            #setter_attr
+            #vis fn #mut_name(&mut self) -> &mut #ty {
+                &mut self.#index
+            }
+        },
+        accessors: vec![mut_name.clone()],
+        debug_value: Some(field.debug_getter()),
+        checks: vec![quote! {
+            overlay::Overlay::validate(&self.#index)?;
+        }],
+        ..Default::default()
+    }
+}
+
+/// A `[u8]` field covering everything after the fixed fields
+fn expand_tail(field: &Field, layout: &mut Layout) -> syn::Result<FieldOutput> {
+    let (ty, vis, attr) = (field.ty, field.vis, field.attr);
+    let (getter_name, mut_name) = (&field.getter_name, &field.mut_name);
+    let setter_attr = setter_attr();
+
+    match ty {
+        Type::Slice(slice) if is_u8(&slice.elem) => {}
+        _ => bail!(ty, "tail fields must be `[u8]`"),
+    }
+    if layout.tail {
+        bail!(attr, "only one tail field is permitted");
+    }
+    if !layout.dynamic_sizes.is_empty() {
+        bail!(attr, "a struct cannot have both tail and len fields");
+    }
+    layout.tail = true;
+
+    Ok(FieldOutput {
+        getter: quote! {
+            #vis fn #getter_name(&self) -> &[u8] {
+                &self.0[Self::BYTE_LEN..]
+            }
+
+            #setter_attr
+            #vis fn #mut_name(&mut self) -> &mut [u8] {
+                &mut self.0[Self::BYTE_LEN..]
+            }
+        },
+        accessors: vec![mut_name.clone()],
+        debug_value: Some(field.debug_getter()),
+        ..Default::default()
+    })
+}
+
+/// A slice of bytes or records, whose length is held by the field `len_field`, following the
+/// fixed fields and any earlier len fields
+fn expand_len(
+    field: &Field,
+    len_field: &Ident,
+    getter_names: &BTreeMap<Ident, Ident>,
+    layout: &mut Layout,
+) -> syn::Result<FieldOutput> {
+    let (name, ty, vis, attr) = (field.name, field.ty, field.vis, field.attr);
+    let (getter_name, mut_name) = (&field.getter_name, &field.mut_name);
+    let setter_attr = setter_attr();
+
+    if layout.tail {
+        bail!(attr, "a struct cannot have both tail and len fields");
+    }
+
+    let elem_ty = match ty {
+        Type::Slice(slice) => &*slice.elem,
+        _ => bail!(
+            ty,
+            "len fields must be a slice: `[u8]` or `[T]` for a nested struct T"
+        ),
+    };
+
+    // sizes come from the data, so are checked for overflow: each is an
+    // `Option<usize>`, and the start is `Err` if it doesn't fit in a `usize`
+    let dynamic_sizes = &layout.dynamic_sizes;
+    let start = quote! {
+        Some(Self::BYTE_LEN)
+            #(.zip(#dynamic_sizes).and_then(|(start, size)| start.checked_add(size)))*
+            .ok_or(overlay::Error::InsufficientLength)?
+    };
+    // an unknown field is reported once every field has been seen
+    let len_getter = getter_names.get(len_field).unwrap_or(len_field);
+    let count = quote! { usize::try_from(self.#len_getter()).ok() };
+
+    let mut items = vec![];
+    let getter = if is_u8(elem_ty) {
+        layout.dynamic_sizes.push(count.clone());
+
+        quote! {
+            #vis fn #getter_name(&self) -> core::result::Result<&[u8], overlay::Error> {
+                let start = #start;
+                let end = #count
+                    .and_then(|len| start.checked_add(len))
+                    .ok_or(overlay::Error::InsufficientLength)?;
+
+                self.0
+                    .get(start..end)
+                    .ok_or(overlay::Error::InsufficientLength)
+            }
+
+            #setter_attr
+            #vis fn #mut_name(&mut self) -> core::result::Result<&mut [u8], overlay::Error> {
+                let start = #start;
+                let end = #count
+                    .and_then(|len| start.checked_add(len))
+                    .ok_or(overlay::Error::InsufficientLength)?;
+
+                self.0
+                    .get_mut(start..end)
+                    .ok_or(overlay::Error::InsufficientLength)
+            }
+        }
+    } else {
+        layout.dynamic_sizes.push(quote! {
+            #count.and_then(|count| count.checked_mul(<#elem_ty>::BYTE_LEN))
+        });
+
+        // records are reinterpreted in place, so must be plain `[u8; N]` wrappers
+        items.push(quote! {
+            const _: () = assert!(
+                core::mem::size_of::<#elem_ty>() == <#elem_ty>::BYTE_LEN
+                    && core::mem::align_of::<#elem_ty>() == 1,
+                concat!("elements of `", stringify!(#name), "` must be fixed-size #[overlay] structs"),
+            );
+        });
+
+        quote! {
+            #vis fn #getter_name(&self) -> core::result::Result<&[#elem_ty], overlay::Error> {
+                let start = #start;
+                let count = #count.ok_or(overlay::Error::InsufficientLength)?;
+                let end = count
+                    .checked_mul(<#elem_ty>::BYTE_LEN)
+                    .and_then(|size| start.checked_add(size))
+                    .ok_or(overlay::Error::InsufficientLength)?;
+
+                let bytes = self
+                    .0
+                    .get(start..end)
+                    .ok_or(overlay::Error::InsufficientLength)?;
+
+                // SAFETY: elements are `[u8; BYTE_LEN]` newtypes (checked above), length checked
+                Ok(unsafe {
+                    core::slice::from_raw_parts(bytes.as_ptr() as *const #elem_ty, count)
+                })
+            }
+
+            #setter_attr
+            #vis fn #mut_name(&mut self) -> core::result::Result<&mut [#elem_ty], overlay::Error> {
+                let start = #start;
+                let count = #count.ok_or(overlay::Error::InsufficientLength)?;
+                let end = count
+                    .checked_mul(<#elem_ty>::BYTE_LEN)
+                    .and_then(|size| start.checked_add(size))
+                    .ok_or(overlay::Error::InsufficientLength)?;
+
+                let bytes = self
+                    .0
+                    .get_mut(start..end)
+                    .ok_or(overlay::Error::InsufficientLength)?;
+
+                // SAFETY: as above
+                Ok(unsafe {
+                    core::slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut #elem_ty, count)
+                })
+            }
+        }
+    };
+
+    Ok(FieldOutput {
+        getter,
+        accessors: vec![mut_name.clone()],
+        debug_value: Some(field.debug_getter()),
+        items,
+        len_field: Some(len_field.clone()),
+        ..Default::default()
+    })
+}
+
+/// A field with a byte range, expanded by the kind of its type
+fn expand_fixed(field: &Field, layout: &mut Layout) -> syn::Result<FieldOutput> {
+    let (name, ty, attr) = (field.name, field.ty, field.attr);
+    let ranges = &field.ranges;
+    let byte_range = field.byte_range();
+
+    let nested = ranges.nested || ranges.kind == Some(Kind::Nested);
+    if nested && ranges.bits.is_some() {
+        bail!(attr, "cannot have a nested struct at a bit-offset");
+    }
+
+    // a checksum's range counts towards the struct's size, as the field's own bytes do
+    let mut extent = byte_range.end_inclusive() as usize + 1;
+    if let Some(checksum) = &ranges.checksum {
+        extent = extent.max(checksum.range.end_inclusive() as usize + 1);
+    }
+    layout.extents.push((name.clone(), extent));
+
+    // bit-packed arrays cover only their elements' bits
+    let packed_bits = match ty {
+        Type::Array(array)
+            if ranges.element_bits.is_some()
+                || matches!(&*array.elem, Type::Path(path) if path.path.is_ident("bool")) =>
+        {
+            // arrays too big for their bytes are reported as the array is expanded
+            u32::try_from(array_len(array)?)
+                .ok()
+                .and_then(|len| len.checked_mul(ranges.element_bits.unwrap_or(1)))
+                .filter(|&packed_bits| packed_bits <= byte_range.len() * 8)
+        }
+        _ => None,
+    };
+    let bits = match (&ranges.bits, packed_bits) {
+        (Some(bits), _) => Some((bits.start(), bits.end_inclusive())),
+        (None, Some(packed_bits)) if packed_bits > 0 => {
+            let start_bit = packed_start_bit(byte_range, packed_bits, field.bit_order);
+            Some((start_bit, start_bit + packed_bits - 1))
+        }
+        _ => None,
+    };
+    layout.coverage.push((
+        name.clone(),
+        ranges.alias,
+        covered_bits(byte_range, bits, field.endian),
+    ));
+
+    if ranges.reserved {
+        return expand_reserved(field);
+    }
+
+    let field_ty = match ranges.kind {
+        Some(kind) => Some(kind_type(ty, kind)?),
+        None => match_type(ty)?,
+    };
+    let field_ty = match field_ty {
+        // `[u8; N]` is a plain byte array unless it's given a stride or element width
+        Some(FieldTy::ByteArray) if ranges.stride.is_some() || ranges.element_bits.is_some() => {
+            match ty {
+                Type::Array(array) => Some(FieldTy::Array {
+                    elem: Box::new(FieldTy::Integer {
+                        signed: Some(false),
+                    }),
+                    len: array_len(array)?,
+                }),
+                // `kind = bytes` on an alias
+                _ => bail!(
+                    attr,
+                    "stride and element_bits need the field's type to be written as an array"
+                ),
+            }
+        }
+        Some(FieldTy::Array { len, .. }) if nested => Some(FieldTy::Array {
+            elem: Box::new(FieldTy::Struct),
+            len,
+        }),
+        _ if nested => Some(FieldTy::Struct),
+        // any type can go through a user-supplied conversion
+        _ if ranges.with.is_some() => Some(FieldTy::Custom),
+        field_ty => field_ty,
+    };
+    let Some(field_ty) = field_ty else {
+        bail!(
+            ty,
+            "invalid field type: expected integer, float, bool, C-style enum, nested struct or array thereof"
+        );
+    };
+
+    if ranges.stride.is_some() && !matches!(field_ty, FieldTy::Array { .. }) {
+        bail!(attr, "stride is only valid for arrays");
+    }
+    if ranges.element_bits.is_some() && !matches!(field_ty, FieldTy::Array { .. }) {
+        bail!(attr, "element_bits is only valid for arrays");
+    }
+    if ranges.unknown.is_some() && !matches!(field_ty, FieldTy::Enum) {
+        bail!(attr, "unknown is only valid for enums");
+    }
+    if ranges.magic.is_some() && !matches!(field_ty, FieldTy::Integer { .. }) {
+        bail!(attr, "magic is only valid for integers");
+    }
+    if ranges.checksum.is_some() && !matches!(field_ty, FieldTy::Integer { .. }) {
+        bail!(attr, "checksum is only valid for integers");
+    }
+
+    let debug_value = if matches!(field_ty, FieldTy::Array { .. }) {
+        let iter_name = format_ident!("{}_iter", name);
+        quote! { &DebugList(|| self.#iter_name()) }
+    } else {
+        field.debug_getter()
+    };
+
+    let mut output = match field_ty {
+        FieldTy::Bool => expand_bool(field)?,
+        FieldTy::Float { bytes } => expand_float(field, bytes)?,
+        FieldTy::Integer { .. } | FieldTy::Enum | FieldTy::Custom => {
+            expand_integer(field, &field_ty)?
+        }
+        FieldTy::Struct => expand_nested(field),
+        FieldTy::ByteArray => expand_byte_array(field)?,
+        FieldTy::Array { elem, len }
+            if ranges.element_bits.is_some() || matches!(*elem, FieldTy::Bool) =>
+        {
+            expand_packed_array(field, &elem, len)?
+        }
+        FieldTy::Array { elem, len } => expand_strided_array(field, &elem, len)?,
+    };
+    output.debug_value = Some(debug_value);
+
+    Ok(output)
+}
+
+/// Bits that have no accessors, and are checked for being zero
+fn expand_reserved(field: &Field) -> syn::Result<FieldOutput> {
+    let (name, attr) = (field.name, field.attr);
+    let byte_range = field.byte_range();
+    let start_byte = byte_range.start() as usize;
+    let end_byte = byte_range.end_inclusive() as usize;
+
+    let masks = match &field.ranges.bits {
+        // whole bytes, so any number of them
+        None => (start_byte..=end_byte).map(|i| (i, 0xff)).collect(),
+        Some(bits) => {
+            let lim = byte_range.len() * 8 - 1;
+            if bits.end_inclusive() > lim {
+                bail!(
+                    attr,
+                    "reserved bits must be inside the byte-range (0..={lim})"
+                );
+            }
+            if byte_range.len() > 16 {
+                bail!(attr, "reserved bits can only be given within 16 bytes");
+            }
+
+            let location = Location::new(
+                attr,
+                start_byte,
+                end_byte,
+                bits.start(),
+                bits.end_inclusive(),
+                field.endian,
+            )?;
+            location.split_bytes(!0_u128 >> (128 - location.width()))
+        }
+    };
+    let (indices, masks): (Vec<_>, Vec<_>) = masks.into_iter().unzip();
+
+    Ok(FieldOutput {
+        reserved: Some((
+            quote! {
+                if #(self.0[#indices] & #masks != 0)||* {
+                    return Err(overlay::Error::ReservedNotZero {
+                        field: stringify!(#name),
+                    });
+                }
+            },
+            quote! {
+                #(self.0[#indices] &= !#masks;)*
+            },
+        )),
+        ..Default::default()
+    })
+}
+
+fn expand_bool(field: &Field) -> syn::Result<FieldOutput> {
+    let (ty, vis, attr) = (field.ty, field.vis, field.attr);
+    let (getter_name, setter_name) = (&field.getter_name, &field.setter_name);
+    let setter_attr = setter_attr();
+    let byte_range = field.byte_range();
+
+    let start_bit = match &field.ranges.bits {
+        None => 0,
+        Some(bits) => {
+            if bits.end_inclusive() != bits.start() {
+                bail!(
+                    attr,
+                    "Bit range for a bool must be a single number (e.g. 1, or 1..=1)"
+                );
+            }
+            bits.start()
+        }
+    };
+
+    if start_bit >= byte_range.len() * 8 {
+        bail!(
+            attr,
+            "bit {start_bit} must be inside the byte-range (0..{})",
+            byte_range.len() * 8
+        );
+    }
+
+    // bits are numbered from the least significant bit of the
+    // whole field, so find the byte that holds it
+    let byte = field.endian.byte_for_bit(
+        byte_range.start() as usize,
+        byte_range.end_inclusive() as usize,
+        start_bit,
+    );
+    let start_bit = start_bit % 8;
+
+    Ok(FieldOutput {
+        getter: quote! {
+            #vis fn #getter_name(&self) -> #ty {
+                let byte = self.0[#byte];
+                (byte >> #start_bit) & 1 != 0
+            }
+        },
+        setter: quote! {
+            #setter_attr
+            #vis fn #setter_name(&mut self, val: #ty) {
+                let bit_value = if val { 1 } else { 0 };
+                self.0[#byte] &= !(1 << #start_bit);
+                self.0[#byte] |= (bit_value << #start_bit) as u8;
+            }
+        },
+        ..Default::default()
+    })
+}
+
+fn expand_float(field: &Field, bytes: usize) -> syn::Result<FieldOutput> {
+    let (ty, vis, attr) = (field.ty, field.vis, field.attr);
+    let (getter_name, setter_name) = (&field.getter_name, &field.setter_name);
+    let setter_attr = setter_attr();
+    let byte_range = field.byte_range();
+
+    if field.ranges.bits.is_some() {
+        bail!(attr, "floats cannot have a bit-range");
+    }
+    if byte_range.len() as usize != bytes {
+        bail!(
+            attr,
+            "{} must occupy {bytes} bytes, not {}",
+            quote! { #ty },
+            byte_range.len()
+        );
+    }
+
+    let location = Location::new(
+        attr,
+        byte_range.start() as usize,
+        byte_range.end_inclusive() as usize,
+        0,
+        bytes as u32 * 8 - 1,
+        field.endian,
+    )?;
+    let getter_body = location.getter_body();
+    let setter_body = location.setter_body(quote! { val.to_bits() });
+
+    Ok(FieldOutput {
+        getter: quote! {
+            #vis fn #getter_name(&self) -> #ty {
+                #getter_body
+
+                #ty::from_bits(value)
+            }
+        },
+        setter: quote! {
+            #setter_attr
+            #vis fn #setter_name(&mut self, val: #ty) {
+                #setter_body
+            }
+        },
+        ..Default::default()
+    })
+}
+
+/// Integers, enums and `with` fields, which are all held as an integer
+fn expand_integer(field: &Field, field_ty: &FieldTy) -> syn::Result<FieldOutput> {
+    let (name, ty, vis, attr) = (field.name, field.ty, field.vis, field.attr);
+    let (getter_name, setter_name) = (&field.getter_name, &field.setter_name);
+    let setter_attr = setter_attr();
+    let ranges = &field.ranges;
+    let byte_range = field.byte_range();
+    let start_byte = byte_range.start() as usize;
+    let end_byte = byte_range.end_inclusive() as usize;
+
+    let lim = (0, byte_range.len() * 8 - 1);
+    let (start_bit, end_bit) = match &ranges.bits {
+        None => lim,
+        Some(bits) => (bits.start(), bits.end_inclusive()),
+    };
+
+    if start_bit > lim.1 || end_bit > lim.1 {
+        bail!(
+            attr,
+            "start and end bits ({start_bit} & {end_bit}) must be inside the byte-range ({}..={})",
+            lim.0,
+            lim.1
+        );
+    }
+
+    let location = Location::new(attr, start_byte, end_byte, start_bit, end_bit, field.endian)?;
+    let getter_body = location.getter_body();
+    let setter_body = location.setter_body(quote! { val });
+    // an unaligned 128-bit field's 17th byte only holds its top bits
+    let value_bytes = (end_byte - start_byte + 1).min(16);
+
+    let mut output = FieldOutput::default();
+    match field_ty {
+        FieldTy::Enum => {
+            output
+                .items
+                .push(enum_fits_check(ty, location.width(), name));
+        }
+        FieldTy::Integer { .. } => output.items.push(int_width_check(
+            ty,
+            location.width(),
+            name,
+            field.warn_wide_types,
+        )?),
+        _ => {}
+    }
+
+    if let Some(magic) = ranges.magic {
+        let width = location.width();
+        if width < 128 && magic >> width != 0 {
+            bail!(
+                attr,
+                "magic value {magic:#x} doesn't fit in the {width} bits of {name}"
+            );
+        }
+
+        output.magic_bytes = location.split_bytes(magic);
+
+        let magic = proc_macro2::Literal::u128_unsuffixed(magic);
+        output.checks.push(quote! {
+            let value = {
+                #getter_body
+                value
+            };
+            if value != #magic {
+                return Err(overlay::Error::BadMagic {
+                    field: stringify!(#name),
+                });
+            }
+        });
+    }
+
+    let getter = if let Some(with) = &ranges.with {
+        // odd sizes, e.g. 3 bytes, round up to the next primitive
+        let raw = enum_repr(value_bytes.next_power_of_two(), attr)?;
+
+        quote! {
+            #vis fn #getter_name(&self) -> #ty {
+                #getter_body
+
+                #with::decode(value as #raw)
+            }
+        }
+    } else if let Some(unknown) = &ranges.unknown {
+        let enum_repr = enum_repr(value_bytes, attr)?;
+
+        quote! {
+            #vis fn #getter_name(&self) -> #ty {
+                #getter_body
+
+                let value = value as #enum_repr;
+                #ty::try_from(value).unwrap_or(#ty::#unknown(value))
+            }
+        }
+    } else if matches!(field_ty, FieldTy::Enum) {
+        let enum_repr = enum_repr(value_bytes, attr)?;
+
+        quote! {
+            #vis fn #getter_name(
+                &self
+            ) -> Result<#ty, <#ty as core::convert::TryFrom<#enum_repr>>::Error> {
+                #getter_body
+
+                let value = value as #enum_repr;
+                #ty::try_from(value)
+            }
+        }
+    } else if let &FieldTy::Integer { signed } = field_ty {
+        let sign_extend = location.sign_extend_for(signed, location.width(), ty);
+
+        quote! {
+            #vis fn #getter_name(&self) -> #ty {
+                #getter_body
+                #sign_extend
+
+                value as _
+            }
+        }
+    } else {
+        quote! {
+            #vis fn #getter_name(&self) -> #ty {
+                #getter_body
+
+                value as _
+            }
+        }
+    };
+
+    // check or convert `val` before it's stored
+    let prepare = match (field_ty, &ranges.with) {
+        (&FieldTy::Integer { signed }, _) => {
+            Location::range_check_for(signed, location.width(), name, ty)
+        }
+        (_, Some(with)) => quote! {
+            let val = #with::encode(val);
+        },
+        // `as` can't convert an enum with data, so the unknown variant needs `From`
+        (FieldTy::Enum, None) if ranges.unknown.is_some() => {
+            let enum_repr = enum_repr(value_bytes, attr)?;
+
+            quote! {
+                let val: #enum_repr = val.into();
+            }
+        }
+        (FieldTy::Enum, None) => {
+            let raw = enum_to_raw(ty, quote! { val });
+
+            quote! {
+                let val = #raw;
+            }
+        }
+        _ => quote! {},
+    };
+
+    output.getter = getter;
+    if let Some(checksum) = &ranges.checksum {
+        let (methods, accessors) = checksum_methods(field, checksum, &location);
+        output.getter.extend(methods);
+        output.accessors = accessors;
+    }
+    output.setter = quote! {
+        #setter_attr
+        #vis fn #setter_name(&mut self, val: #ty) {
+            #prepare
+            #setter_body
+        }
+    };
+
+    Ok(output)
+}
+
+/// `verify_x()` and `update_x()` for a checksum field at `location`, and their names
+fn checksum_methods(
+    field: &Field,
+    checksum: &Checksum,
+    location: &Location,
+) -> (TokenStream, Vec<Ident>) {
+    let (name, vis) = (field.name, field.vis);
+    let setter_attr = setter_attr();
+    let getter_body = location.getter_body();
+    let setter_body = location.setter_body(quote! { val });
+
+    let start = checksum.range.start() as usize;
+    let end = checksum.range.end_inclusive() as usize + 1;
+
+    // the algorithms run over the covered bytes with the field zeroed
+    let (indices, masks): (Vec<_>, Vec<_>) = location
+        .split_bytes(!0_u128 >> (128 - location.width()))
+        .into_iter()
+        .filter(|&(i, _)| (start..end).contains(&i))
+        .map(|(i, mask)| (i - start, mask))
+        .unzip();
+    let data = quote! {
+        let mut data = [0_u8; #end - #start];
+        data.copy_from_slice(&self.0[#start..#end]);
+        #(data[#indices] &= !#masks;)*
+    };
+
+    let algorithm = &checksum.algorithm;
+    let acc = location.accumulator();
+    let width_mask = proc_macro2::Literal::u128_unsuffixed(!0_u128 >> (128 - location.width()));
+    let verify_name = format_ident!("verify_{}", name);
+    let update_name = format_ident!("update_{}", name);
+
+    (
+        quote! {
+            #setter_attr
+            #vis fn #verify_name(&self) -> bool {
+                #data
+                #getter_body
+
+                // as the setter, keep only the bits that fit
+                value == #algorithm(&data) as #acc & #width_mask
+            }
+
+            #setter_attr
+            #vis fn #update_name(&mut self) {
+                #data
+                let val = #algorithm(&data);
+
+                #setter_body
+            }
+        },
+        vec![verify_name, update_name],
+    )
+}
+
+/// ORs `<ty as Overlay>::INIT` into `init`, at the bytes from `base` up to `len` of them
+fn nested_init(ty: &Type, base: TokenStream, len: TokenStream) -> TokenStream {
+    quote! {
+        let nested = <#ty as overlay::Overlay>::INIT;
+        let mut i = 0;
+        while i < nested.len() && i < #len {
+            init[#base + i] |= nested[i];
+            i += 1;
+        }
+    }
+}
+
+/// A nested struct, modified through a `_mut()` accessor
+fn expand_nested(field: &Field) -> FieldOutput {
+    let (ty, vis) = (field.ty, field.vis);
+    let (getter_name, mut_name) = (&field.getter_name, &field.mut_name);
+    let setter_attr = setter_attr();
+    let byte_range = field.byte_range();
+    let start_byte = byte_range.start() as usize;
+    let end_byte = byte_range.end_inclusive() as usize;
+
+    FieldOutput {
+        getter: quote! {
+            #vis fn #getter_name(&self) -> &#ty {
+                let p = &self.0[#start_byte..=#end_byte];
+
+                // could make this unsafe
+                overlay::Overlay::overlay(p).unwrap()
+            }
+
+            #setter_attr
+            #vis fn #mut_name(&mut self) -> &mut #ty {
+                let p = &mut self.0[#start_byte..=#end_byte];
+
+                overlay::Overlay::overlay_mut(p).unwrap()
+            }
+        },
+        accessors: vec![mut_name.clone()],
+        checks: vec![quote! {
+            overlay::Overlay::validate(self.#getter_name())?;
+        }],
+        init: vec![nested_init(
+            ty,
+            quote! { #start_byte },
+            quote! { #end_byte - #start_byte + 1 },
+        )],
+        ..Default::default()
+    }
+}
+
+fn expand_byte_array(field: &Field) -> syn::Result<FieldOutput> {
+    let (ty, vis, attr) = (field.ty, field.vis, field.attr);
+    let (getter_name, setter_name) = (&field.getter_name, &field.setter_name);
+    let setter_attr = setter_attr();
+    let byte_range = field.byte_range();
+    let start_byte = byte_range.start() as usize;
+    let end_byte = byte_range.end_inclusive() as usize;
+
+    if field.ranges.bits.is_some() {
+        bail!(attr, "byte arrays cannot have a bit-range");
+    }
+
+    Ok(FieldOutput {
+        getter: quote! {
+            #vis fn #getter_name(&self) -> &#ty {
+                return self
+                    .0[#start_byte..=#end_byte]
+                    .try_into()
+                    .unwrap(); // could make this unsafe and drop the try
+            }
+        },
+        setter: quote! {
+            #setter_attr
+            #vis fn #setter_name(&mut self, bytes: &#ty) {
+                self.0[#start_byte..=#end_byte]
+                    .copy_from_slice(bytes);
+            }
+        },
+        ..Default::default()
+    })
+}
+
+/// An array of integers, enums or bools, each `element_bits` wide, packed into a bit-range
+fn expand_packed_array(field: &Field, elem: &FieldTy, len: usize) -> syn::Result<FieldOutput> {
+    let (name, ty, vis, attr) = (field.name, field.ty, field.vis, field.attr);
+    let (getter_name, setter_name) = (&field.getter_name, &field.setter_name);
+    let setter_attr = setter_attr();
+    let ranges = &field.ranges;
+    let byte_range = field.byte_range();
+
+    if ranges.stride.is_some() {
+        bail!(attr, "bit-packed arrays cannot have a stride");
+    }
+
+    let element_bits = ranges.element_bits.unwrap_or(1);
+    if element_bits == 0 || element_bits > 128 {
+        bail!(attr, "element_bits must be between 1 and 128");
+    }
+
+    let bit_len = byte_range.len() * 8;
+    if len == 0 || byte_range.len() > 16 {
+        bail!(
+            attr,
+            "bit-packed arrays must have elements, within 16 bytes"
+        );
+    }
+    // at most 128 bits fit, so anything that overflows doesn't
+    let packed_bits = u32::try_from(len)
+        .ok()
+        .and_then(|len| len.checked_mul(element_bits));
+    let start_bit = match (&ranges.bits, packed_bits) {
+        (Some(bits), _) => Some(bits.start()),
+        (None, Some(packed_bits)) => {
+            Some(packed_start_bit(byte_range, packed_bits, field.bit_order))
+        }
+        (None, None) => None,
+    };
+    let end_bit = start_bit
+        .zip(packed_bits)
+        .and_then(|(start_bit, packed_bits)| start_bit.checked_add(packed_bits - 1));
+    let (Some(start_bit), Some(end_bit)) = (start_bit, end_bit) else {
+        bail!(
+            attr,
+            "{len} {element_bits}-bit elements don't fit in {bit_len} bits"
+        );
+    };
+    if let Some(bits) = &ranges.bits {
+        if bits.end_inclusive() != end_bit {
+            bail!(
+                attr,
+                "bit-range ({start_bit}..={}) must hold exactly {len} {element_bits}-bit elements ({start_bit}..={end_bit})",
+                bits.end_inclusive(),
+            );
+        }
+    }
+    if end_bit >= bit_len {
+        bail!(
+            attr,
+            "{len} {element_bits}-bit elements don't fit in {bit_len} bits"
+        );
+    }
+
+    let location = Location::new(
+        attr,
+        byte_range.start() as usize,
+        byte_range.end_inclusive() as usize,
+        start_bit,
+        end_bit,
+        field.endian,
+    )?;
+    let acc = location.accumulator();
+    let getter_body = location.getter_body();
+    let setter_body = location.setter_body(quote! { value });
+    let elem_mask = proc_macro2::Literal::u128_unsuffixed(!0_u128 >> (128 - element_bits));
+
+    let elem_ty = match ty {
+        Type::Array(array) => &*array.elem,
+        _ => unreachable!(),
+    };
+    let iter_name = format_ident!("{}_iter", name);
+    let len_name = format_ident!("{}_len", name);
+    let index_check = quote! {
+        assert!(
+            i < #len,
+            "index {} out of range for `{}` (length {})",
+            i,
+            stringify!(#name),
+            #len,
+        );
+    };
+    // element 0 is at the start of the range: its least significant bits with
+    // lsb0, or its most significant with msb0, as a diagram would show it
+    let shift = if field.bit_order == Some(BitOrder::Msb0) {
+        quote! { (#len - 1 - i) * #element_bits as usize }
+    } else {
+        quote! { i * #element_bits as usize }
+    };
+
+    let mut items = vec![];
+    // check or convert `val` before it's stored
+    let mut prepare = quote! {};
+    let (ret_ty, convert) = match *elem {
+        FieldTy::Bool => (quote! { bool }, quote! { value != 0 }),
+        FieldTy::Integer { signed } => {
+            items.push(int_width_check(
+                elem_ty,
+                element_bits,
+                name,
+                field.warn_wide_types,
+            )?);
+            prepare = Location::range_check_for(signed, element_bits, name, elem_ty);
+            let sign_extend = location.sign_extend_for(signed, element_bits, elem_ty);
+
+            (
+                quote! { #elem_ty },
+                quote! {
+                    #sign_extend
+                    value as _
+                },
+            )
+        }
+        FieldTy::Enum => {
+            let enum_repr = enum_repr(
+                (element_bits as usize).div_ceil(8).next_power_of_two(),
+                attr,
+            )?;
+            items.push(enum_fits_check(elem_ty, element_bits, name));
+            let raw = enum_to_raw(elem_ty, quote! { val });
+            prepare = quote! {
+                let val = #raw;
+            };
+
+            (
+                quote! {
+                    Result<#elem_ty, <#elem_ty as core::convert::TryFrom<#enum_repr>>::Error>
+                },
+                quote! {
+                    let value = value as #enum_repr;
+                    #elem_ty::try_from(value)
+                },
+            )
+        }
+        _ => bail!(
+            ty,
+            "invalid bit-packed array element type: expected integer, bool or C-style enum"
+        ),
+    };
+
+    Ok(FieldOutput {
+        getter: quote! {
+            #vis fn #getter_name(&self, i: usize) -> #ret_ty {
+                #index_check
+                #getter_body
+
+                let value = (value >> (#shift)) & #elem_mask;
+                #convert
+            }
+
+            #vis fn #iter_name(&self) -> impl Iterator<Item = #ret_ty> + '_ {
+                (0..#len).map(move |i| self.#getter_name(i))
+            }
+
+            #vis fn #len_name(&self) -> usize {
+                #len
+            }
+        },
+        setter: quote! {
+            #setter_attr
+            #vis fn #setter_name(&mut self, i: usize, val: #elem_ty) {
+                #index_check
+                #prepare
+                #getter_body
+
+                let shift = #shift;
+                let value = (value & !(#elem_mask << shift))
+                    | (((val as #acc) & #elem_mask) << shift);
+
+                #setter_body
+            }
+        },
+        accessors: vec![iter_name, len_name],
+        items,
+        ..Default::default()
+    })
+}
+
+/// An array of integers, floats, enums or nested structs, each element starting `stride` bytes
+/// after the previous one
+fn expand_strided_array(field: &Field, elem: &FieldTy, len: usize) -> syn::Result<FieldOutput> {
+    let (name, ty, vis, attr) = (field.name, field.ty, field.vis, field.attr);
+    let (getter_name, setter_name, mut_name) =
+        (&field.getter_name, &field.setter_name, &field.mut_name);
+    let setter_attr = setter_attr();
+    let byte_range = field.byte_range();
+    let start_byte = byte_range.start() as usize;
+
+    if field.ranges.bits.is_some() {
+        bail!(attr, "arrays cannot have a bit-range");
+    }
+
+    let byte_len = byte_range.len() as usize;
+    let stride = match field.ranges.stride {
+        Some(stride) => stride as usize,
+        None => {
+            if len == 0 || !byte_len.is_multiple_of(len) {
+                bail!(
+                    attr,
+                    "can't divide {byte_len} bytes between {len} elements, specify a stride"
+                );
+            }
+            byte_len / len
+        }
+    };
+    if stride == 0 {
+        bail!(attr, "stride must be non-zero");
+    }
+    // the offset of the last element, if it's in range
+    let last = match len {
+        0 => Some(0),
+        _ => (len - 1).checked_mul(stride),
+    };
+    let Some(last) = last.filter(|&last| last < byte_len) else {
+        bail!(
+            attr,
+            "{len} elements with a stride of {stride} don't fit in {byte_len} bytes"
+        );
+    };
+
+    let iter_name = format_ident!("{}_iter", name);
+    let len_name = format_ident!("{}_len", name);
+    let index_check = quote! {
+        assert!(
+            i < #len,
+            "index {} out of range for `{}` (length {})",
+            i,
+            stringify!(#name),
+            #len,
+        );
+        let base = #start_byte + i * #stride;
+    };
+
+    let common = quote! {
+        #vis fn #len_name(&self) -> usize {
+            #len
+        }
+    };
+
+    let elem_ty = match ty {
+        Type::Array(array) => &*array.elem,
+        _ => unreachable!(),
+    };
+
+    let mut output = FieldOutput::default();
+    match *elem {
+        FieldTy::Integer { .. } | FieldTy::Float { .. } | FieldTy::Enum => {
+            // integers and floats are sized by their type, and followed by any
+            // padding up to the next element; enums, and integers whose size only
+            // the compiler knows, fill the stride
+            let elem_bytes = match *elem {
+                FieldTy::Float { bytes } => bytes,
+                FieldTy::Integer { .. } => {
+                    int_bits(elem_ty).map_or(stride, |bits| bits as usize / 8)
+                }
+                _ => stride,
+            };
+            if elem_bytes > stride {
+                bail!(
+                    attr,
+                    "elements of {} are {elem_bytes} bytes, so need a stride of at least {elem_bytes}, not {stride}",
+                    quote! { #ty },
+                );
+            }
+            if elem_bytes > 16 {
+                bail!(
+                    attr,
+                    "{elem_bytes}-byte array elements are not supported (maximum is 16 bytes)"
+                );
+            }
+            if len > 0 && last + elem_bytes > byte_len {
+                bail!(
+                    attr,
+                    "{len} elements with a stride of {stride} don't fit in {byte_len} bytes"
+                );
+            }
+
+            let location = Location {
+                base: Some(quote! { base }),
+                ..Location::new(
+                    attr,
+                    0,
+                    elem_bytes - 1,
+                    0,
+                    elem_bytes as u32 * 8 - 1,
+                    field.endian,
+                )?
+            };
+            let getter_body = location.getter_body();
+            let setter_body = location.setter_body(match *elem {
+                FieldTy::Float { .. } => quote! { val.to_bits() },
+                FieldTy::Enum => enum_to_raw(elem_ty, quote! { val }),
+                _ => quote! { val },
+            });
+
+            let (ret_ty, convert) = match *elem {
+                FieldTy::Float { .. } => {
+                    (quote! { #elem_ty }, quote! { #elem_ty::from_bits(value) })
+                }
+                FieldTy::Enum => {
+                    let enum_repr = enum_repr(elem_bytes, attr)?;
+                    output
+                        .items
+                        .push(enum_fits_check(elem_ty, elem_bytes as u32 * 8, name));
+                    (
+                        quote! {
+                            Result<#elem_ty, <#elem_ty as core::convert::TryFrom<#enum_repr>>::Error>
+                        },
+                        quote! {
+                            let value = value as #enum_repr;
+                            #elem_ty::try_from(value)
+                        },
+                    )
+                }
+                FieldTy::Integer { signed } => {
+                    output.items.push(int_width_check(
+                        elem_ty,
+                        location.width(),
+                        name,
+                        field.warn_wide_types,
+                    )?);
+                    let sign_extend = location.sign_extend_for(signed, location.width(), elem_ty);
+                    (
+                        quote! { #elem_ty },
+                        quote! {
+                            #sign_extend
+                            value as _
+                        },
+                    )
+                }
+                _ => unreachable!(),
+            };
+
+            output.getter = quote! {
+                #vis fn #getter_name(&self, i: usize) -> #ret_ty {
+                    #index_check
+                    #getter_body
+                    #convert
+                }
+
+                #vis fn #iter_name(&self) -> impl Iterator<Item = #ret_ty> + '_ {
+                    (0..#len).map(move |i| self.#getter_name(i))
+                }
+
+                #common
+            };
+            output.setter = quote! {
+                #setter_attr
+                #vis fn #setter_name(&mut self, i: usize, val: #elem_ty) {
+                    #index_check
+                    #setter_body
+                }
+            };
+            output.accessors = vec![iter_name, len_name];
+        }
+        FieldTy::Struct => {
+            output.items.push(quote! {
+                const _: () = assert!(
+                    <#elem_ty>::BYTE_LEN <= #stride
+                        && #last + <#elem_ty>::BYTE_LEN <= #byte_len,
+                    concat!("elements of `", stringify!(#name), "` don't fit in its stride/byte-range"),
+                );
+            });
+
+            // as with nested structs, elements are modified through `_mut()`
+            output.getter = quote! {
+                #vis fn #getter_name(&self, i: usize) -> &#elem_ty {
+                    #index_check
+                    let p = &self.0[base..base + <#elem_ty>::BYTE_LEN];
+
+                    overlay::Overlay::overlay(p).unwrap()
+                }
+
+                #setter_attr
+                #vis fn #mut_name(&mut self, i: usize) -> &mut #elem_ty {
+                    #index_check
+                    let p = &mut self.0[base..base + <#elem_ty>::BYTE_LEN];
+
+                    overlay::Overlay::overlay_mut(p).unwrap()
+                }
+
+                #vis fn #iter_name(&self) -> impl Iterator<Item = &#elem_ty> + '_ {
+                    (0..#len).map(move |i| self.#getter_name(i))
+                }
+
+                #common
+            };
+            output.accessors = vec![iter_name, len_name, mut_name.clone()];
+            output.checks.push(quote! {
+                for i in 0..#len {
+                    overlay::Overlay::validate(self.#getter_name(i))?;
+                }
+            });
+            let init = nested_init(
+                elem_ty,
+                quote! { #start_byte + n * #stride },
+                quote! { <#elem_ty>::BYTE_LEN },
+            );
+            output.init.push(quote! {
+                let mut n = 0;
+                while n < #len {
+                    #init
+                    n += 1;
+                }
+            });
+        }
+        _ => bail!(
+            ty,
+            "invalid array element type: expected integer, float, C-style enum or nested struct"
+        ),
+    }
+
+    Ok(output)
+}
@@ -1,25 +1,145 @@
-use std::ops::{Range, RangeInclusive};
+use std::{
+    collections::BTreeMap,
+    ops::{Range, RangeInclusive},
+};
 
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::{
+    ext::IdentExt,
     parse::{Parse, ParseStream},
-    parse_macro_input, Data, DeriveInput, Fields, Ident, LitInt, Meta, NestedMeta, Token, Type,
+    parse_macro_input,
+    spanned::Spanned,
+    Data, DeriveInput, Fields, Ident, LitInt, Meta, NestedMeta, Token, Type,
 };
 
+/// Returns a `syn::Error` spanning `tokens`, with a `format!`ted message
+macro_rules! bail {
+    ($tokens:expr, $($message:tt)*) => {
+        return Err(syn::Error::new_spanned($tokens, format!($($message)*)))
+    };
+}
+
+mod enums;
+mod fields;
+mod tagged;
+
+/// The methods of `overlay::Overlay`, which an accessor of the same name would shadow
+const OVERLAY_METHODS: [&str; 5] = [
+    "overlay",
+    "overlay_mut",
+    "validate",
+    "overlay_checked",
+    "overlay_checked_mut",
+];
+
+/// Fields must end before this byte, so that the bits of a struct can be counted in a `u32`
+const MAX_BYTES: u32 = u32::MAX / 8;
+
 enum FieldTy {
-    Integer,
+    Integer {
+        /// `None` when only the compiler knows, e.g. for a type alias
+        signed: Option<bool>,
+    },
+    Float {
+        bytes: usize,
+    },
     Bool,
     Enum,
     Struct,
     ByteArray,
+    /// Converted to/from its raw integer by a user-supplied module
+    Custom,
+    Array {
+        elem: Box<FieldTy>,
+        len: usize,
+    },
 }
 
-#[derive(Debug)]
 struct OverlayAttribute {
-    byte: SingleOrRange,
+    /// Always present, except for tail and len fields, and auto-layout fields until they're laid out
+    byte: Option<SingleOrRange>,
     bits: Option<SingleOrRange>,
     nested: bool,
+    endian: Option<Endian>,
+    stride: Option<u32>,
+    element_bits: Option<u32>,
+    tail: bool,
+    len: Option<Ident>,
+    /// Module providing `decode(raw) -> T` and `encode(T) -> raw`
+    with: Option<syn::Path>,
+    /// Enum variant holding raw values that don't convert, e.g. `Other` for `Other(u8)`
+    unknown: Option<Ident>,
+    /// Fixed value of the field, set by `new()` and checked by `validate()`
+    magic: Option<u128>,
+    /// No accessors; the bits should be zero
+    reserved: bool,
+    checksum: Option<Checksum>,
+    /// Permits the field to overlap others, e.g. a word and its sub-fields
+    alias: bool,
+    /// Width in bits of an auto-layout field, placed after the previous field
+    width: Option<u32>,
+    bit_order: Option<BitOrder>,
+    /// Start of the field in bits from the start of the struct, ignoring byte boundaries
+    bit_offset: Option<u32>,
+    /// Width in bits of a `bit_offset` field
+    bit_len: Option<u32>,
+    /// Sized by the struct's parameters, and placed after the fixed fields
+    generic: bool,
+    /// Names of the accessors, in place of `x()` and `set_x()`/`x_mut()`
+    getter: Option<Ident>,
+    setter: Option<Ident>,
+    /// How to treat the field's type, in place of guessing from its name
+    kind: Option<Kind>,
+}
+
+/// `algorithm(range)`, e.g. `crc32(0..60)`
+struct Checksum {
+    /// A function from `&[u8]` to an unsigned integer
+    algorithm: syn::Path,
+    range: SingleOrRange,
+}
+
+#[derive(Default)]
+struct StructAttribute {
+    endian: Option<Endian>,
+    /// Whether `validate()` also checks that reserved fields are zero
+    reject_reserved: bool,
+    /// Fixed `BYTE_LEN`, rather than the end of the last field
+    size: Option<usize>,
+    bit_order: Option<BitOrder>,
+    /// Prepended to the name of each getter, e.g. `get_` for `get_x()`
+    getter_prefix: Option<Ident>,
+    /// Whether to warn about integer fields with a type wider than needed
+    warn_wide_types: bool,
+    /// Location of the discriminant, for enums
+    tag: Option<OverlayAttribute>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endian {
+    Big,
+    Little,
+}
+
+/// How `bits` are numbered within a field's byte range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitOrder {
+    /// Bit 0 is the least significant bit of the field's value
+    Lsb0,
+    /// Bit 0 is the most significant bit of the field's value, i.e. of the first byte when
+    /// big-endian, as in RFC packet diagrams
+    Msb0,
+}
+
+/// `kind = ...`, for types whose name doesn't say what they are, e.g. `type Lba = u32;`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Int,
+    Bool,
+    Enum,
+    Nested,
+    Bytes,
 }
 
 #[derive(Debug)]
@@ -29,237 +149,321 @@ enum SingleOrRange {
     RangeIncl(RangeInclusive<u32>),
 }
 
+/// Generates `TryFrom` and `From` conversions between a C-style enum and every unsigned integer,
+/// for use as a field of an [`overlay`](macro@overlay) struct. See the `overlay` docs for details.
+#[proc_macro_attribute]
+pub fn overlay_enum(macro_attrs: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+
+    enums::conversions(macro_attrs.into(), item).into()
+}
+
 #[doc = include_str!("../README.md")]
 #[proc_macro_attribute]
 pub fn overlay(macro_attrs: TokenStream, item: TokenStream) -> TokenStream {
-    assert!(macro_attrs.is_empty(), "No attributes expected");
+    let input = parse_macro_input!(item as DeriveInput);
+    let placeholder = placeholder(&input);
+
+    match syn::parse(macro_attrs).and_then(|struct_attrs| expand(struct_attrs, input)) {
+        Ok(expanded) => expanded.into(),
+        Err(error) => {
+            let error = error.to_compile_error();
+
+            TokenStream::from(quote! {
+                #placeholder
+                #error
+            })
+        }
+    }
+}
 
-    let mut input = parse_macro_input!(item as DeriveInput);
-    let name = input.ident;
+/// Stands in for the struct or enum when it has errors, so that they aren't followed by errors
+/// about it being missing
+fn placeholder(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let DeriveInput {
+        vis,
+        ident: name,
+        generics,
+        ..
+    } = input;
+
+    if matches!(input.data, Data::Enum(_)) && generics.params.is_empty() {
+        let mut_name = format_ident!("{}Mut", name);
+
+        return quote! {
+            #vis struct #name<'a>(core::marker::PhantomData<&'a ()>);
+            #vis struct #mut_name<'a>(core::marker::PhantomData<&'a ()>);
+        };
+    }
 
-    if !input.generics.params.is_empty() {
-        return TokenStream::from(quote! {
-            compile_error!("Generics are not supported");
-        });
+    let phantoms = generics
+        .lifetimes()
+        .map(|param| {
+            let lifetime = &param.lifetime;
+            quote! { &#lifetime () }
+        })
+        .chain(generics.type_params().map(|param| {
+            let param = &param.ident;
+            quote! { #param }
+        }));
+    let where_clause = &generics.where_clause;
+
+    quote! {
+        #vis struct #name #generics(#(core::marker::PhantomData<#phantoms>),*) #where_clause;
     }
+}
 
-    let fields = if let Data::Struct(data_struct) = input.data {
-        if let Fields::Named(fields) = data_struct.fields {
-            fields.named
-        } else {
-            unimplemented!("only named-field structs are supported")
+/// Merges `errors`, so that they're all reported together
+fn combine_errors(errors: Vec<syn::Error>) -> syn::Result<()> {
+    let combined = errors.into_iter().reduce(|mut combined, error| {
+        combined.combine(error);
+        combined
+    });
+
+    match combined {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn expand(
+    struct_attrs: StructAttribute,
+    mut input: DeriveInput,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let name = input.ident;
+
+    let generics = input.generics.clone();
+    if let Some(lifetime) = generics.lifetimes().next() {
+        bail!(lifetime, "lifetime parameters are not supported");
+    }
+    if !generics.params.is_empty() && !matches!(input.data, Data::Struct(_)) {
+        bail!(&generics, "only structs can have type and const parameters");
+    }
+    // fields whose type uses one of these are sized by the parameters
+    let params: Vec<Ident> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .chain(generics.const_params().map(|param| param.ident.clone()))
+        .collect();
+
+    let fields = match input.data {
+        Data::Struct(data_struct) => {
+            if let Fields::Named(fields) = data_struct.fields {
+                fields.named
+            } else {
+                bail!(&name, "only structs with named fields are supported");
+            }
         }
-    } else {
-        unimplemented!("only structs can be overlaid")
+        Data::Enum(data_enum) => {
+            return tagged::overlay_enum(struct_attrs, name, input.vis, input.attrs, data_enum);
+        }
+        Data::Union(_) => bail!(&name, "only structs and enums can be overlaid"),
     };
 
+    if struct_attrs.tag.is_some() {
+        bail!(&name, "tag is only valid for enums");
+    }
+
     let mut getters = vec![];
     let mut setters = vec![];
     let mut field_names = vec![];
-    let mut last_byte = 0;
-    for field in fields {
-        let field_name = field.ident.expect("named field");
+    let mut debug_names = vec![];
+    let mut debug_values = vec![];
+    let mut items = vec![];
+    let mut layout = fields::Layout::default();
+    let mut len_fields = vec![];
+    // `(index, value)` of each non-zero byte of the magic fields
+    let mut magic_bytes = vec![];
+    // run to build `Overlay::INIT`
+    let mut inits = vec![];
+    // run by `validate()`
+    let mut checks = vec![];
+    // run by `check_reserved()` and `clear_reserved()`
+    let mut reserved_checks = vec![];
+    let mut reserved_clears = vec![];
+    // `(accessor, field)` of each generated method, checked for collisions
+    let mut accessors = vec![];
+    // reported together, once every field has been seen
+    let mut errors = vec![];
+
+    // len fields read their length through another field's getter, which may be renamed
+    let getter_prefix = struct_attrs.getter_prefix.as_ref();
+    let getter_names: BTreeMap<Ident, Ident> = fields
+        .iter()
+        .filter_map(|field| {
+            let field_name = field.ident.as_ref()?;
+            let attr = field
+                .attrs
+                .iter()
+                .find(|attr| attr.path.is_ident("overlay"))?;
+            // errors are reported as the field is expanded
+            let ranges = attr.parse_args::<OverlayAttribute>().ok()?;
+
+            Some((
+                field_name.clone(),
+                ranges.getter_name(field_name, getter_prefix),
+            ))
+        })
+        .collect();
+
+    let uses_params = fields.iter().any(|field| {
+        let ty = &field.ty;
+        mentions_any(quote! { #ty }, &params)
+    });
+
+    for field in &fields {
+        let field_name = field.ident.clone().expect("named field");
         field_names.push(field_name.clone());
 
         let mut found = false;
-        for attr in field.attrs {
+        for attr in &field.attrs {
             const ATTR_NAME: &str = "overlay";
             if attr.path.is_ident(ATTR_NAME) {
                 found = true;
 
-                let ranges: OverlayAttribute = attr.parse_args().unwrap();
+                let output = match fields::expand_field(
+                    field,
+                    attr,
+                    &struct_attrs,
+                    &params,
+                    &getter_names,
+                    &mut layout,
+                ) {
+                    Ok(output) => output,
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    }
+                };
 
-                let nested = ranges.nested;
-                ranges.byte.assert_range_valid("byte");
-                if let Some(bits) = &ranges.bits {
-                    bits.assert_range_valid("bit");
-                    assert!(!nested, "cannot have a nested struct at a bit-offset");
+                getters.push(output.getter);
+                setters.push(output.setter);
+                accessors.extend(
+                    output
+                        .accessors
+                        .into_iter()
+                        .map(|accessor| (accessor, field_name.clone())),
+                );
+                if let Some(debug_value) = output.debug_value {
+                    debug_names.push(field_name.clone());
+                    debug_values.push(debug_value);
                 }
+                items.extend(output.items);
+                magic_bytes.extend(output.magic_bytes);
+                inits.extend(output.init);
+                checks.extend(output.checks);
+                if let Some((check, clear)) = output.reserved {
+                    reserved_checks.push(check);
+                    reserved_clears.push(clear);
+                }
+                len_fields.extend(output.len_field);
+            }
+        }
 
-                last_byte = last_byte.max(ranges.byte.end_inclusive());
-
-                let ty = &field.ty;
-                let vis = &field.vis;
-
-                let field_ty = if nested {
-                    Some(FieldTy::Struct)
-                } else {
-                    match_type(ty)
-                }.expect("invalid field type: expected integer, bool, C-style enum, nested struct or [u8; N]");
-
-                // e.g. `_x: u8` -> `set__x()`
-                //                       ^ rustc warns about this
-                let setter_attr = quote! { #[allow(non_snake_case)] };
-
-                let start_byte = ranges.byte.start() as usize;
-                let end_byte = ranges.byte.end_inclusive() as usize;
-
-                let setter_name = format_ident!("set_{}", field_name);
-
-                let (getter, setter) = match field_ty {
-                    FieldTy::Bool => {
-                        let start_bit = match &ranges.bits {
-                            None => 0,
-                            Some(bits) => {
-                                if bits.end_inclusive() != bits.start() {
-                                    panic!("Bit range for a bool must be a single number (e.g. 1, or 1..=1)")
-                                }
-                                bits.start()
-                            }
-                        };
-
-                        (
-                            quote! {
-                                #vis fn #field_name(&self) -> bool {
-                                    let byte = self.0[#start_byte];
-                                    (byte >> #start_bit) & 1 != 0
-                                }
-                            },
-                            quote! {
-                                #setter_attr
-                                #vis fn #setter_name(&mut self, val: bool) {
-                                    let bit_value = if val { 1 } else { 0 };
-                                    self.0[#start_byte] &= !(1 << #start_bit);
-                                    self.0[#start_byte] |= (bit_value << #start_bit) as u8;
-                                }
-                            },
-                        )
-                    }
-                    FieldTy::Integer | FieldTy::Enum => {
-                        let lim = (0, ranges.byte.len() * 8 - 1);
-                        let (start_bit, end_bit) = match &ranges.bits {
-                            None => lim,
-                            Some(bits) => (bits.start(), bits.end_inclusive()),
-                        };
-
-                        if start_bit > lim.1 || end_bit > lim.1 {
-                            panic!(
-                                "start and end bits ({start_bit} & {end_bit}) must be inside the byte-range ({}..={})",
-                                lim.0,
-                                lim.1
-                            );
-                        }
+        if !found {
+            errors.push(syn::Error::new(
+                field_name.span(),
+                format!("No #[overlay(...)] attribute found for {field_name}"),
+            ));
+        }
+    }
+    let fields::Layout {
+        extents,
+        coverage,
+        tail,
+        dynamic_sizes,
+        generic_tys,
+        ..
+    } = layout;
+    let mut byte_count = extents.iter().map(|(_, end)| *end).max().unwrap_or(0);
+
+    // `(field, alias, bits)` of each fixed field
+    for (i, (a, a_alias, a_bits)) in coverage.iter().enumerate() {
+        for (b, b_alias, b_bits) in &coverage[i + 1..] {
+            if *a_alias || *b_alias {
+                continue;
+            }
 
-                        let getter_body = quote! {
-                            let mut value = 0_u32;
-                            for i in #start_byte..=#end_byte {
-                                value <<= 8;
-                                value |= self.0[i] as u32;
-                            }
+            let overlap = a_bits
+                .iter()
+                .any(|(byte, mask)| b_bits.get(byte).is_some_and(|other| mask & other != 0));
+            if overlap {
+                for (field, other) in [(a, b), (b, a)] {
+                    let message = format!(
+                        "`{field}` overlaps `{other}`; mark one of them `alias` if this is intended"
+                    );
+                    errors.push(syn::Error::new(field.span(), message));
+                }
+            }
+        }
+    }
 
-                            // mask off end_bit..
-                            if #end_bit > 0 {
-                                value &= !0_u32 >> (31 - #end_bit);
-                            }
+    if let Some(size) = struct_attrs.size {
+        for (field, end) in &extents {
+            if *end > size {
+                let message = format!(
+                    "`{field}` ends at byte {}, outside the struct's size of {size} bytes",
+                    end - 1,
+                );
+                errors.push(syn::Error::new(field.span(), message));
+            }
+        }
+        byte_count = size;
+    }
 
-                            value >>= #start_bit;
-                        };
+    // the struct is only declared with its parameters if a generic field uses them
+    if !params.is_empty() && !uses_params {
+        errors.push(syn::Error::new_spanned(
+            &generics,
+            "type and const parameters must be used by an #[overlay(generic)] field",
+        ));
+    }
 
-                        (
-                            if matches!(field_ty, FieldTy::Enum) {
-                                let enum_repr = match end_byte - start_byte + 1 {
-                                    1 => quote! { u8 },
-                                    2 => quote! { u16 },
-                                    4 => quote! { u32 },
-                                    8 => quote! { u64 },
-                                    size => {
-                                        panic!("can't determine size of field for {size}-byte enum")
-                                    }
-                                };
-
-                                quote! {
-                                    #vis fn #field_name(
-                                        &self
-                                    ) -> Result<#ty, <#ty as core::convert::TryFrom<#enum_repr>>::Error> {
-                                        #getter_body
-
-                                        let value = value as #enum_repr;
-                                        #ty::try_from(value)
-                                    }
-                                }
-                            } else {
-                                quote! {
-                                    #vis fn #field_name(&self) -> #ty {
-                                        #getter_body
-
-                                        value as _
-                                    }
-                                }
-                            },
-                            quote! {
-                                #setter_attr
-                                #vis fn #setter_name(&mut self, val: #ty) {
-                                    let mut mask = !0_u32 << #start_bit;
-                                    if #end_bit > 0 {
-                                        mask &= !0_u32 >> (31 - #end_bit);
-                                    }
-
-                                    let mut new = ((val as u32) << #start_bit) & mask;
-
-                                    for i in (#start_byte..=#end_byte).rev() {
-                                        self.0[i] = self.0[i] & (!mask as u8) | (new as u8);
-                                        new >>= 8;
-                                        mask >>= 8;
-                                    }
-                                }
-                            },
-                        )
-                    }
-                    FieldTy::Struct => {
-                        let setter_name = format_ident!("{}_mut", field_name);
-
-                        (
-                            quote! {
-                                #vis fn #field_name(&self) -> &#ty {
-                                    let p = &self.0[#start_byte..=#end_byte];
-
-                                    // could make this unsafe
-                                    overlay::Overlay::overlay(p).unwrap()
-                                }
-
-                                #setter_attr
-                                #vis fn #setter_name(&mut self) -> &mut #ty {
-                                    let p = &mut self.0[#start_byte..=#end_byte];
-
-                                    overlay::Overlay::overlay_mut(p).unwrap()
-                                }
-                            },
-                            // setter isn't provided, we just expose a &mut to the nested struct (above, with the getter)
-                            quote! {},
-                        )
-                    }
-                    FieldTy::ByteArray => {
-                        assert!(ranges.bits.is_none(), "byte arrays cannot have a bit-range");
-
-                        (
-                            quote! {
-                                #vis fn #field_name(&self) -> &#ty {
-                                    return self
-                                        .0[#start_byte..=#end_byte]
-                                        .try_into()
-                                        .unwrap(); // could make this unsafe and drop the try
-                                }
-                            },
-                            quote! {
-                                #setter_attr
-                                #vis fn #setter_name(&mut self, bytes: &#ty) {
-                                    self.0[#start_byte..=#end_byte]
-                                        .copy_from_slice(bytes);
-                                }
-                            },
-                        )
-                    }
-                };
-                getters.push(getter);
-                setters.push(setter);
-            }
+    for len_field in &len_fields {
+        if !field_names.contains(len_field) {
+            errors.push(syn::Error::new(
+                len_field.span(),
+                format!("len refers to unknown field {len_field}"),
+            ));
         }
+    }
 
-        assert!(
-            found,
-            "No #[overlay(...)] attribute found for {}",
-            field_name
-        );
+    // the struct's own items, alongside the accessors in its `impl`
+    let mut own_items = vec!["as_bytes", "as_bytes_mut", "BYTE_LEN"];
+    if !tail && dynamic_sizes.is_empty() {
+        own_items.push("new");
+    }
+    if !reserved_checks.is_empty() {
+        own_items.extend(["check_reserved", "clear_reserved"]);
+    }
+    if !generic_tys.is_empty() {
+        own_items.push("ALIGN_CHECK");
+    }
+    for (i, (accessor, field)) in accessors.iter().enumerate() {
+        let message = if own_items.contains(&accessor.to_string().as_str()) {
+            format!("`{accessor}` of `{field}` collides with the struct's own `{accessor}`")
+        } else if OVERLAY_METHODS.contains(&accessor.to_string().as_str()) {
+            format!("`{accessor}` of `{field}` would shadow `Overlay::{accessor}`")
+        } else if let Some((_, other)) = accessors[..i].iter().find(|(prev, _)| prev == accessor) {
+            format!("`{accessor}` of `{field}` collides with the `{accessor}` of `{other}`")
+        } else {
+            continue;
+        };
+        errors.push(syn::Error::new(
+            accessor.span(),
+            format!("{message}; rename it with `getter = ..` or `setter = ..`"),
+        ));
+    }
+    combine_errors(errors)?;
+
+    // generic fields are reinterpreted in place, so the impls need them to be valid for any bytes,
+    // and to be `Overlay`s to be validated and initialised
+    let mut bounded = generics.clone();
+    for ty in &generic_tys {
+        bounded
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote! { #ty: overlay::FromAnyBytes + overlay::Overlay });
     }
 
     let mut implement_debug = false;
@@ -287,11 +491,37 @@ pub fn overlay(macro_attrs: TokenStream, item: TokenStream) -> TokenStream {
     }
 
     let debug_impl = if implement_debug {
+        // as `#[derive(Debug)]` does, require each type parameter to be `Debug`
+        let mut debug_generics = bounded.clone();
+        for param in generics.type_params() {
+            let param = &param.ident;
+            debug_generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote! { #param: core::fmt::Debug });
+        }
+        let (impl_generics, ty_generics, where_clause) = debug_generics.split_for_impl();
+
         quote! {
-            impl core::fmt::Debug for #name {
+            impl #impl_generics core::fmt::Debug for #name #ty_generics #where_clause {
                 fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    // array fields are shown via their iterators
+                    #[allow(dead_code)]
+                    struct DebugList<F>(F);
+
+                    impl<F, I> core::fmt::Debug for DebugList<F>
+                    where
+                        F: Fn() -> I,
+                        I: Iterator,
+                        I::Item: core::fmt::Debug,
+                    {
+                        fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                            fmt.debug_list().entries((self.0)()).finish()
+                        }
+                    }
+
                     fmt.debug_struct(stringify!(#name))
-                        #(.field(stringify!(#field_names), &self.#field_names()))*
+                        #(.field(stringify!(#debug_names), #debug_values))*
                         .finish()
                 }
             }
@@ -300,44 +530,172 @@ pub fn overlay(macro_attrs: TokenStream, item: TokenStream) -> TokenStream {
         quote! {}
     };
 
-    let byte_count = last_byte as usize + 1;
     let vis = input.vis;
     let attrs = input.attrs;
 
+    let reserved = if reserved_checks.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            /// Checks that every reserved field is zero
+            pub fn check_reserved(&self) -> core::result::Result<(), overlay::Error> {
+                #(#reserved_checks)*
+
+                Ok(())
+            }
+
+            /// Zeroes every reserved field
+            pub fn clear_reserved(&mut self) {
+                #(#reserved_clears)*
+            }
+        }
+    };
+    if struct_attrs.reject_reserved && !reserved_checks.is_empty() {
+        checks.push(quote! { self.check_reserved()?; });
+    }
+
+    let (indices, values): (Vec<_>, Vec<_>) = magic_bytes.into_iter().unzip();
+    inits.push(quote! {
+        #(init[#indices] |= #values;)*
+    });
+    let init = quote! {
+        const INIT: &'static [u8] = &{
+            let mut init = [0_u8; #byte_count];
+            #({ #inits })*
+
+            init
+        };
+    };
+
+    // generic fields follow the fixed bytes, so are initialised separately
+    let generic_inits = generic_tys.iter().enumerate().map(|(i, ty)| {
+        let index = syn::Index::from(i + 1);
+
+        quote! {
+            let nested = <#ty as overlay::Overlay>::INIT;
+            let p = &mut this.#index as *mut #ty as *mut u8;
+            let mut i = 0;
+            while i < nested.len() && i < core::mem::size_of::<#ty>() {
+                // SAFETY: inside the field, which is bytes (`FromAnyBytes`)
+                unsafe { *p.add(i) |= nested[i] };
+                i += 1;
+            }
+        }
+    });
+
+    let declared_where_clause = &generics.where_clause;
+    let (impl_generics, ty_generics, where_clause) = bounded.split_for_impl();
+    let is_generic = !generic_tys.is_empty();
+    if is_generic && (tail || !dynamic_sizes.is_empty()) {
+        bail!(&name, "generic structs cannot have tail or len fields");
+    }
+
+    // with a tail or len field, the struct is unsized and covers the whole of the overlaid slice
+    let (storage, new) = if tail || !dynamic_sizes.is_empty() {
+        (quote! { [u8] }, quote! {})
+    } else {
+        (
+            quote! { [u8; #byte_count] },
+            quote! {
+                pub const fn new() -> Self {
+                    // SAFETY: all fields are POD (specifically int/bool/array thereof),
+                    // and all-zero bit-pattern is valid for these.
+                    // For enums, if the bit-pattern isn't valid, it's caught when we
+                    // attempt to read that field, via `try_from`.
+                    let mut this: Self = unsafe {
+                        use core::mem;
+                        mem::zeroed()
+                    };
+
+                    // magic fields start out holding their value, as do those of nested structs
+                    let init = <Self as overlay::Overlay>::INIT;
+                    let mut i = 0;
+                    while i < init.len() {
+                        this.0[i] = init[i];
+                        i += 1;
+                    }
+                    #({ #generic_inits })*
+
+                    this
+                }
+            },
+        )
+    };
+
+    // the size of a generic struct isn't known until it's instantiated, so it can't be a single
+    // array; instead the generic fields follow the fixed bytes as separate byte-aligned members
+    let (declaration, byte_len, bytes_methods, align_check) = if is_generic {
+        (
+            quote! {
+                #[repr(C)]
+                #vis struct #name #generics(#storage, #(#generic_tys),*) #declared_where_clause;
+            },
+            quote! { core::mem::size_of::<Self>() },
+            quote! {
+                pub fn as_bytes(&self) -> &[u8] {
+                    // SAFETY: every member is bytes, with no padding as the alignment is 1
+                    unsafe {
+                        core::slice::from_raw_parts(self as *const Self as *const u8, Self::BYTE_LEN)
+                    }
+                }
+
+                pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+                    // SAFETY: as for `as_bytes()`
+                    unsafe {
+                        core::slice::from_raw_parts_mut(self as *mut Self as *mut u8, Self::BYTE_LEN)
+                    }
+                }
+
+                const ALIGN_CHECK: () = assert!(
+                    core::mem::align_of::<Self>() == 1,
+                    concat!("the generic fields of ", stringify!(#name), " must be #[overlay] structs or byte arrays"),
+                );
+            },
+            quote! { let () = Self::ALIGN_CHECK; },
+        )
+    } else {
+        (
+            quote! {
+                #[repr(transparent)]
+                #vis struct #name(#storage);
+            },
+            quote! { #byte_count },
+            quote! {
+                pub fn as_bytes(&self) -> &#storage {
+                    &self.0
+                }
+
+                pub fn as_bytes_mut(&mut self) -> &mut #storage {
+                    &mut self.0
+                }
+            },
+            quote! {},
+        )
+    };
+
     let expanded = quote! {
         #(#attrs)*
-        #[repr(transparent)]
-        #vis struct #name([u8; #byte_count]);
+        #declaration
 
-        impl #name {
+        impl #impl_generics #name #ty_generics #where_clause {
             #(#getters)*
             #(#setters)*
 
-            pub fn as_bytes(&self) -> &[u8; #byte_count] {
-                &self.0
-            }
+            #bytes_methods
 
-            pub fn as_bytes_mut(&mut self) -> &mut [u8; #byte_count] {
-                &mut self.0
-            }
+            #new
 
-            pub const fn new() -> Self {
-                // SAFETY: all fields are POD (specifically int/bool/array thereof),
-                // and all-zero bit-pattern is valid for these.
-                // For enums, if the bit-pattern isn't valid, it's caught when we
-                // attempt to read that field, via `try_from`.
-                unsafe {
-                    use core::mem;
-                    mem::zeroed()
-                }
-            }
+            #reserved
 
-            pub const BYTE_LEN: usize = #byte_count;
+            pub const BYTE_LEN: usize = #byte_len;
         }
 
-        impl overlay::Overlay for #name {
+        impl #impl_generics overlay::Overlay for #name #ty_generics #where_clause {
+            #init
+
             fn overlay(bytes: &[u8]) -> core::result::Result<&Self, overlay::Error> {
-                if bytes.len() < #byte_count {
+                #align_check
+                if bytes.len() < Self::BYTE_LEN {
                     return Err(overlay::Error::InsufficientLength);
                 }
 
@@ -347,7 +705,8 @@ pub fn overlay(macro_attrs: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             fn overlay_mut(bytes: &mut [u8]) -> core::result::Result<&mut Self, overlay::Error> {
-                if bytes.len() < #byte_count {
+                #align_check
+                if bytes.len() < Self::BYTE_LEN {
                     return Err(overlay::Error::InsufficientLength);
                 }
 
@@ -356,60 +715,485 @@ pub fn overlay(macro_attrs: TokenStream, item: TokenStream) -> TokenStream {
                 Ok(unsafe { &mut *p })
             }
 
+            fn validate(&self) -> core::result::Result<(), overlay::Error> {
+                #(#checks)*
+
+                Ok(())
+            }
         }
 
+        // SAFETY: the struct is bytes, followed by any generic fields, which are bound above to
+        // be valid for any bytes with an alignment of 1
+        unsafe impl #impl_generics overlay::FromAnyBytes for #name #ty_generics #where_clause {}
+
         #debug_impl
+
+        #(#items)*
     };
 
-    TokenStream::from(expanded)
+    Ok(expanded)
 }
 
-fn match_type(ty: &Type) -> Option<FieldTy> {
+fn match_type(ty: &Type) -> syn::Result<Option<FieldTy>> {
     match ty {
         Type::Path(path) => {
             let segment = path.path.segments.last().unwrap();
-            return Some(match segment.ident.to_string().as_str() {
-                "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "u128" | "i128"
-                | "usize" | "isize" => FieldTy::Integer,
+            return Ok(Some(match segment.ident.to_string().as_str() {
+                "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => FieldTy::Integer {
+                    signed: Some(false),
+                },
+                "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => {
+                    FieldTy::Integer { signed: Some(true) }
+                }
+                "f32" => FieldTy::Float { bytes: 4 },
+                "f64" => FieldTy::Float { bytes: 8 },
                 "bool" => FieldTy::Bool,
                 _ => FieldTy::Enum,
-            });
+            }));
         }
         Type::Array(array) => {
             if let Type::Path(ref path) = *array.elem {
                 if path.path.segments.last().unwrap().ident == "u8" {
-                    return Some(FieldTy::ByteArray);
+                    return Ok(Some(FieldTy::ByteArray));
                 }
             }
+
+            let Some(elem) = match_type(&array.elem)? else {
+                return Ok(None);
+            };
+            return Ok(Some(FieldTy::Array {
+                elem: Box::new(elem),
+                len: array_len(array)?,
+            }));
         }
         _ => {}
     }
 
-    None
+    Ok(None)
+}
+
+/// The field type given by an explicit `kind`. For arrays, other than of bytes, the kind is that
+/// of the elements.
+fn kind_type(ty: &Type, kind: Kind) -> syn::Result<FieldTy> {
+    if let Type::Array(array) = ty {
+        if kind != Kind::Bytes {
+            return Ok(FieldTy::Array {
+                elem: Box::new(kind_type(&array.elem, kind)?),
+                len: array_len(array)?,
+            });
+        }
+    }
+
+    // values are converted with `as`, so wrappers such as `Wrapping<u8>` can't be used
+    let plain = match ty {
+        Type::Path(path) => {
+            path.qself.is_none()
+                && path
+                    .path
+                    .segments
+                    .iter()
+                    .all(|segment| segment.arguments.is_none())
+        }
+        _ => false,
+    };
+    if kind == Kind::Int && !plain {
+        bail!(
+            ty,
+            "kind = int needs a primitive integer or an alias of one; convert other types with `with`"
+        );
+    }
+
+    Ok(match kind {
+        // a primitive's signedness is known from its name, an alias's only once compiled
+        Kind::Int => match match_type(ty)? {
+            Some(FieldTy::Integer { signed }) => FieldTy::Integer { signed },
+            _ => FieldTy::Integer { signed: None },
+        },
+        Kind::Bool => FieldTy::Bool,
+        Kind::Enum => FieldTy::Enum,
+        Kind::Nested => FieldTy::Struct,
+        Kind::Bytes => FieldTy::ByteArray,
+    })
+}
+
+fn is_u8(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.path.is_ident("u8"),
+        _ => false,
+    }
+}
+
+fn array_len(array: &syn::TypeArray) -> syn::Result<usize> {
+    match &array.len {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(len),
+            ..
+        }) => len.base10_parse(),
+        len => bail!(len, "array length must be an integer literal"),
+    }
+}
+
+/// The byte range and bits of a `width`-bit field starting `cursor` bits into the struct. Big-endian
+/// fields fill each byte from its most significant bit, and little-endian from its least, so that
+/// the field's bits are contiguous either way.
+fn auto_layout(cursor: u32, width: u32, endian: Endian) -> (SingleOrRange, Option<SingleOrRange>) {
+    let first = cursor / 8;
+    let last = (cursor + width - 1) / 8;
+    let byte = SingleOrRange::RangeIncl(first..=last);
+
+    if cursor.is_multiple_of(8) && width.is_multiple_of(8) {
+        return (byte, None);
+    }
+
+    // bits are numbered from the least significant bit of the whole range
+    let offset = cursor - first * 8;
+    let bit_len = (last - first + 1) * 8;
+    let start_bit = match endian {
+        Endian::Big => bit_len - offset - width,
+        Endian::Little => offset,
+    };
+
+    (
+        byte,
+        Some(SingleOrRange::RangeIncl(start_bit..=start_bit + width - 1)),
+    )
+}
+
+/// The lsb0 bit a bit-packed array without `bits` starts at: the bottom of its byte range, or
+/// with msb0, as far up as its `packed_bits` allow
+fn packed_start_bit(
+    byte_range: &SingleOrRange,
+    packed_bits: u32,
+    bit_order: Option<BitOrder>,
+) -> u32 {
+    match bit_order {
+        // too many bits are reported by the field's codegen
+        Some(BitOrder::Msb0) => (byte_range.len() * 8).saturating_sub(packed_bits),
+        _ => 0,
+    }
+}
+
+/// Whether any of `idents` appear in `tokens`, e.g. a generic parameter in a field's type
+fn mentions_any(tokens: proc_macro2::TokenStream, idents: &[Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => idents.contains(&ident),
+        proc_macro2::TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
+/// Byte index -> mask of the bits a field covers, for overlap checks. Without `bits`, the whole of
+/// the byte range is covered.
+fn covered_bits(
+    byte_range: &SingleOrRange,
+    bits: Option<(u32, u32)>,
+    endian: Endian,
+) -> BTreeMap<usize, u8> {
+    let start_byte = byte_range.start() as usize;
+    let end_byte = byte_range.end_inclusive() as usize;
+
+    let mut covered = BTreeMap::new();
+    match bits {
+        None => {
+            for i in start_byte..=end_byte {
+                covered.insert(i, 0xff);
+            }
+        }
+        Some((start_bit, end_bit)) => {
+            // out-of-range bits are reported by the field's codegen
+            let end_bit = end_bit.min(byte_range.len() * 8 - 1);
+            for bit in start_bit..=end_bit {
+                let byte = endian.byte_for_bit(start_byte, end_byte, bit);
+                *covered.entry(byte).or_insert(0) |= 1 << (bit % 8);
+            }
+        }
+    }
+
+    covered
+}
+
+/// Asserts that the largest value of an `#[overlay_enum]` fits in the field's bits. Other enums
+/// pass, via a fallback that only applies when `OverlayEnum` isn't implemented.
+fn enum_fits_check(ty: &Type, width: u32, field_name: &Ident) -> proc_macro2::TokenStream {
+    let max = proc_macro2::Literal::u128_unsuffixed(!0_u128 >> (128 - width));
+    let width = proc_macro2::Literal::u32_unsuffixed(width);
+
+    quote! {
+        const _: () = {
+            trait Fallback {
+                const MAX_RAW: u128 = 0;
+            }
+            struct Check<T: ?Sized>(core::marker::PhantomData<T>);
+            impl<T: ?Sized> Fallback for Check<T> {}
+            impl<T: ?Sized + overlay::OverlayEnum> Check<T> {
+                const MAX_RAW: u128 = T::MAX_RAW;
+            }
+
+            assert!(
+                <Check<#ty>>::MAX_RAW <= #max,
+                concat!("values of ", stringify!(#ty), " don't fit in the ", #width, " bits of `", stringify!(#field_name), "`"),
+            );
+        };
+    }
+}
+
+/// `val`, an enum of type `ty`, as something `as` can convert to an integer: its raw value if it's
+/// an `#[overlay_enum]`, as `as` can't convert variants carrying data, or otherwise the enum
+/// itself, via a fallback that only applies when `OverlayEnum` isn't implemented.
+fn enum_to_raw(ty: &Type, val: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {{
+        trait Fallback<T> {
+            fn to_raw(self) -> T;
+        }
+        struct Raw<T>(T);
+        impl<T> Fallback<T> for Raw<T> {
+            fn to_raw(self) -> T {
+                self.0
+            }
+        }
+        impl<T: overlay::OverlayEnum> Raw<T> {
+            fn to_raw(self) -> u128 {
+                overlay::OverlayEnum::to_raw(self.0)
+            }
+        }
+
+        Raw::<#ty>(#val).to_raw()
+    }}
+}
+
+/// The width of a primitive integer type, from its name. That of `usize`, `isize` or an alias is
+/// only known once compiled.
+fn int_bits(ty: &Type) -> Option<u32> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    Some(
+        match path
+            .path
+            .segments
+            .last()
+            .unwrap()
+            .ident
+            .to_string()
+            .as_str()
+        {
+            "u8" | "i8" => 8,
+            "u16" | "i16" => 16,
+            "u32" | "i32" => 32,
+            "u64" | "i64" => 64,
+            "u128" | "i128" => 128,
+            _ => return None,
+        },
+    )
+}
+
+/// Reports an integer type too narrow for the field's `width` bits on the type. With `warn_wide`,
+/// a type wider than needed produces a warning, through a deprecated item.
+fn int_width_check(
+    ty: &Type,
+    width: u32,
+    field_name: &Ident,
+    warn_wide: bool,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let name = match ty {
+        Type::Path(path) => path.path.segments.last().unwrap().ident.to_string(),
+        _ => return Ok(quote! {}),
+    };
+    let Some(bits) = int_bits(ty) else {
+        let width = proc_macro2::Literal::u32_unsuffixed(width);
+        return Ok(quote_spanned! {ty.span()=>
+            const _: () = assert!(
+                <#ty>::BITS >= #width,
+                concat!(stringify!(#ty), " can't hold the ", #width, " bits of `", stringify!(#field_name), "`"),
+            );
+        });
+    };
+
+    if bits < width {
+        bail!(ty, "{name} can't hold the {width} bits of `{field_name}`");
+    }
+
+    let needed = width.next_power_of_two().max(8);
+    if warn_wide && needed < bits {
+        let note = format!(
+            "`{field_name}` only needs {width} bits, so {}{needed} would do in place of {name}",
+            &name[..1],
+        );
+
+        // the use is spanned on the type, so the warning points there
+        return Ok(quote_spanned! {ty.span()=>
+            const _: () = {
+                #[deprecated(note = #note)]
+                const fn wide_type() {}
+                wide_type()
+            };
+        });
+    }
+
+    Ok(quote! {})
+}
+
+/// The primitive used for an enum's `TryFrom`, from the number of bytes it occupies
+fn enum_repr(byte_len: usize, attr: &syn::Attribute) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(match byte_len {
+        1 => quote! { u8 },
+        2 => quote! { u16 },
+        4 => quote! { u32 },
+        8 => quote! { u64 },
+        16 => quote! { u128 },
+        size => bail!(attr, "can't determine size of field for {size}-byte enum"),
+    })
 }
 
 impl Parse for OverlayAttribute {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let (mut byte, mut bits, mut nested) = (None, None, false);
+        let (mut byte, mut bits, mut nested, mut endian, mut stride, mut element_bits, mut tail) =
+            (None, None, false, None, None, None, false);
+        let mut len = None;
+        let mut with = None;
+        let mut unknown = None;
+        let mut magic = None;
+        let mut reserved = false;
+        let mut checksum = None;
+        let mut alias = false;
+        let mut width = None;
+        let mut bit_order = None;
+        let (mut bit_offset, mut bit_len) = (None, None);
+        let mut generic = false;
+        let (mut getter, mut setter) = (None, None);
+        let mut kind = None;
+
+        // the whole attribute, for errors about combinations of specifiers
+        let tokens = input.fork().parse::<proc_macro2::TokenStream>()?;
 
         loop {
             if input.is_empty() {
                 break;
             }
 
-            let keyword = input.parse::<Ident>()?.to_string();
+            let keyword = input.parse::<Ident>()?;
 
             if keyword == "nested" {
                 nested = true;
+            } else if keyword == "tail" {
+                tail = true;
+            } else if keyword == "reserved" {
+                reserved = true;
+            } else if keyword == "alias" {
+                alias = true;
+            } else if keyword == "generic" {
+                generic = true;
+            } else if keyword == "unknown" {
+                input.parse::<Token![=]>()?;
+
+                if unknown.replace(input.parse::<Ident>()?).is_some() {
+                    bail!(&keyword, "duplicate specifier for unknown");
+                }
+            } else if keyword == "magic" {
+                input.parse::<Token![=]>()?;
+
+                let value = input.parse::<LitInt>()?.base10_parse()?;
+                if magic.replace(value).is_some() {
+                    bail!(&keyword, "duplicate specifier for magic");
+                }
+            } else if keyword == "bit_offset" || keyword == "bit_len" {
+                input.parse::<Token![=]>()?;
+
+                let value = input.parse::<LitInt>()?.base10_parse()?;
+                let old = if keyword == "bit_offset" {
+                    bit_offset.replace(value)
+                } else {
+                    bit_len.replace(value)
+                };
+                if old.is_some() {
+                    bail!(&keyword, "duplicate specifier for {keyword}");
+                }
+            } else if keyword == "bit_order" {
+                input.parse::<Token![=]>()?;
+
+                if bit_order.replace(input.parse()?).is_some() {
+                    bail!(&keyword, "duplicate specifier for bit_order");
+                }
+            } else if keyword == "checksum" {
+                input.parse::<Token![=]>()?;
+
+                let algorithm = input.parse::<syn::Path>()?;
+                let content;
+                syn::parenthesized!(content in input);
+                let range = content.parse::<SingleOrRange>()?;
+                if matches!(range, SingleOrRange::Single(_)) || !content.is_empty() {
+                    bail!(
+                        &keyword,
+                        "checksum needs a range of bytes, e.g. crc32(0..60)"
+                    );
+                }
+
+                if checksum.replace(Checksum { algorithm, range }).is_some() {
+                    bail!(&keyword, "duplicate specifier for checksum");
+                }
+            } else if keyword == "getter" || keyword == "setter" {
+                input.parse::<Token![=]>()?;
+
+                let name = input.parse::<Ident>()?;
+                let old = if keyword == "getter" {
+                    getter.replace(name)
+                } else {
+                    setter.replace(name)
+                };
+                if old.is_some() {
+                    bail!(&keyword, "duplicate specifier for {keyword}");
+                }
+            } else if keyword == "kind" {
+                input.parse::<Token![=]>()?;
+
+                if kind.replace(input.parse()?).is_some() {
+                    bail!(&keyword, "duplicate specifier for kind");
+                }
+            } else if keyword == "with" {
+                input.parse::<Token![=]>()?;
+
+                if with.replace(input.parse::<syn::Path>()?).is_some() {
+                    bail!(&keyword, "duplicate specifier for with");
+                }
+            } else if keyword == "len" {
+                input.parse::<Token![=]>()?;
+
+                if len.replace(input.parse::<Ident>()?).is_some() {
+                    bail!(&keyword, "duplicate specifier for len");
+                }
+            } else if keyword == "le" || keyword == "be" {
+                let old = endian.replace(if keyword == "le" {
+                    Endian::Little
+                } else {
+                    Endian::Big
+                });
+                if old.is_some() {
+                    bail!(&keyword, "duplicate endian specifier");
+                }
+            } else if keyword == "stride" {
+                input.parse::<Token![=]>()?;
+
+                let value = input.parse::<LitInt>()?.base10_parse()?;
+                if stride.replace(value).is_some() {
+                    bail!(&keyword, "duplicate specifier for stride");
+                }
+            } else if keyword == "element_bits" {
+                input.parse::<Token![=]>()?;
+
+                let value = input.parse::<LitInt>()?.base10_parse()?;
+                if element_bits.replace(value).is_some() {
+                    bail!(&keyword, "duplicate specifier for element_bits");
+                }
             } else {
                 input.parse::<Token![=]>()?;
 
-                let (is_byte, is_singular) = match keyword.as_str() {
+                let (is_byte, is_singular) = match keyword.to_string().as_str() {
                     "byte" => (true, true),
                     "bytes" => (true, false),
                     "bit" => (false, true),
                     "bits" => (false, false),
-                    _ => panic!("invalid specifier {keyword}"),
+                    _ => bail!(&keyword, "invalid specifier {keyword}"),
                 };
 
                 let span: SingleOrRange = input.parse()?;
@@ -417,10 +1201,28 @@ impl Parse for OverlayAttribute {
                 match (is_singular, &span) {
                     (true, SingleOrRange::Single(_))
                     | (false, SingleOrRange::RangeIncl(_) | SingleOrRange::Range(_)) => {}
-                    _ => {
-                        // TODO: compile_error!()
-                        panic!("invalid combination of {keyword} and single/range span");
+                    // `bytes = N` or `bits = N`: a width, for auto-layout
+                    (false, &SingleOrRange::Single(n)) => {
+                        let n = if is_byte { n.checked_mul(8) } else { Some(n) };
+                        let Some(n) = n else {
+                            bail!(&keyword, "fields must end before byte {MAX_BYTES}");
+                        };
+                        if width.replace(n).is_some() {
+                            bail!(&keyword, "duplicate width");
+                        }
+                        if input.parse::<Token![,]>().is_err() {
+                            break;
+                        }
+                        continue;
                     }
+                    _ => bail!(
+                        &keyword,
+                        "{keyword} takes a single number, use {keyword}s for a range"
+                    ),
+                }
+
+                if is_byte && span.end_inclusive() >= MAX_BYTES {
+                    bail!(&keyword, "fields must end before byte {MAX_BYTES}");
                 }
 
                 let old = if is_byte {
@@ -429,7 +1231,7 @@ impl Parse for OverlayAttribute {
                     bits.replace(span)
                 };
                 if old.is_some() {
-                    panic!("duplicate specifier for {keyword}");
+                    bail!(&keyword, "duplicate specifier for {keyword}");
                 }
             }
 
@@ -439,17 +1241,558 @@ impl Parse for OverlayAttribute {
         }
 
         if !input.is_empty() {
-            panic!("unused tokens");
+            return Err(input.error("expected `,`"));
+        }
+
+        if tail && len.is_some() {
+            bail!(&tokens, "a field cannot be both tail and len");
+        }
+        if reserved && (getter.is_some() || setter.is_some()) {
+            bail!(&tokens, "reserved fields have no accessors to rename");
+        }
+        if magic.is_some() && setter.is_some() {
+            bail!(&tokens, "magic fields have no setter to rename");
+        }
+        if (tail || len.is_some()) && kind.is_some() {
+            bail!(
+                &tokens,
+                "tail and len fields are slices, so cannot have a kind"
+            );
+        }
+        if nested && kind.is_some() {
+            bail!(
+                &tokens,
+                "nested is short for `kind = nested`, so cannot be combined with a kind"
+            );
+        }
+        if with.is_some() && kind.is_some() {
+            bail!(
+                &tokens,
+                "with converts any type, so cannot be combined with a kind"
+            );
+        }
+        if bit_offset.is_some() != bit_len.is_some() {
+            bail!(&tokens, "bit_offset and bit_len must be given together");
+        }
+
+        if generic {
+            if byte.is_some() || bits.is_some() || width.is_some() || bit_offset.is_some() {
+                bail!(
+                    &tokens,
+                    "generic fields follow the fixed fields, so cannot have a byte or bit range"
+                );
+            }
+        } else if tail || len.is_some() {
+            if byte.is_some() || bits.is_some() || width.is_some() || bit_offset.is_some() {
+                bail!(
+                    &tokens,
+                    "tail and len fields cannot have a byte or bit range"
+                );
+            }
+        } else if bit_offset.is_some() {
+            if byte.is_some() || bits.is_some() || width.is_some() {
+                bail!(&tokens, "bit_offset and bit_len cannot be combined with a byte or bit range, or a width");
+            }
+            if bit_len == Some(0) {
+                bail!(&tokens, "a field's width cannot be zero");
+            }
+        } else if width.is_some() {
+            if byte.is_some() || bits.is_some() {
+                bail!(&tokens, "a width (`bytes = N` or `bits = N`) cannot be combined with a byte or bit range");
+            }
+            if width == Some(0) {
+                bail!(&tokens, "a field's width cannot be zero");
+            }
+        } else if byte.is_none() {
+            bail!(&tokens, "no byte specifier");
         }
 
         Ok(Self {
-            byte: byte.expect("no byte specifier"),
+            byte,
             bits,
             nested,
+            endian,
+            stride,
+            element_bits,
+            tail,
+            len,
+            with,
+            unknown,
+            magic,
+            reserved,
+            checksum,
+            alias,
+            width,
+            bit_order,
+            bit_offset,
+            bit_len,
+            generic,
+            getter,
+            setter,
+            kind,
+        })
+    }
+}
+
+impl OverlayAttribute {
+    /// The name of the field's getter, given the struct's `getter_prefix`
+    fn getter_name(&self, field_name: &Ident, prefix: Option<&Ident>) -> Ident {
+        match (&self.getter, prefix) {
+            (Some(getter), _) => getter.clone(),
+            (None, Some(prefix)) => {
+                format_ident!("{}{}", prefix, field_name, span = field_name.span())
+            }
+            (None, None) => field_name.clone(),
+        }
+    }
+
+    /// Renumbers `bits` from the least significant bit, as the codegen expects
+    fn renumber_bits(&mut self, default_order: Option<BitOrder>) {
+        if self.bit_order.or(default_order) != Some(BitOrder::Msb0) {
+            return;
+        }
+
+        if let (Some(byte), Some(bits)) = (&self.byte, &self.bits) {
+            // bits outside the byte range are left as given, for the field's checks to report
+            let lim = byte.len() * 8 - 1;
+            if bits.end_inclusive() > lim {
+                return;
+            }
+
+            self.bits = Some(match bits {
+                &SingleOrRange::Single(bit) => SingleOrRange::Single(lim - bit),
+                _ => SingleOrRange::RangeIncl(lim - bits.end_inclusive()..=lim - bits.start()),
+            });
+        }
+    }
+}
+
+impl Parse for BitOrder {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let value = input.parse::<Ident>()?;
+        Ok(match value.to_string().as_str() {
+            "lsb0" => BitOrder::Lsb0,
+            "msb0" => BitOrder::Msb0,
+            _ => bail!(
+                &value,
+                "invalid bit_order {value}, expected `lsb0` or `msb0`"
+            ),
+        })
+    }
+}
+
+impl Parse for Kind {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // `enum` is a keyword
+        let value = input.call(Ident::parse_any)?;
+        Ok(match value.to_string().as_str() {
+            "int" => Kind::Int,
+            "bool" => Kind::Bool,
+            "enum" => Kind::Enum,
+            "nested" => Kind::Nested,
+            "bytes" => Kind::Bytes,
+            _ => bail!(
+                &value,
+                "invalid kind {value}, expected `int`, `bool`, `enum`, `nested` or `bytes`"
+            ),
         })
     }
 }
 
+impl Parse for StructAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+
+        loop {
+            if input.is_empty() {
+                break;
+            }
+
+            let keyword = input.parse::<Ident>()?;
+
+            match keyword.to_string().as_str() {
+                "tag" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    if attrs.tag.replace(content.parse()?).is_some() {
+                        bail!(&keyword, "duplicate specifier for tag");
+                    }
+                }
+                "bit_order" => {
+                    input.parse::<Token![=]>()?;
+                    if attrs.bit_order.replace(input.parse()?).is_some() {
+                        bail!(&keyword, "duplicate specifier for bit_order");
+                    }
+                }
+                "size" => {
+                    input.parse::<Token![=]>()?;
+                    let value = input.parse::<LitInt>()?.base10_parse()?;
+                    if attrs.size.replace(value).is_some() {
+                        bail!(&keyword, "duplicate specifier for size");
+                    }
+                }
+                "getter_prefix" => {
+                    input.parse::<Token![=]>()?;
+                    if attrs.getter_prefix.replace(input.parse()?).is_some() {
+                        bail!(&keyword, "duplicate specifier for getter_prefix");
+                    }
+                }
+                "warn_wide_types" => {
+                    attrs.warn_wide_types = true;
+                }
+                "reject_reserved" => {
+                    attrs.reject_reserved = true;
+                }
+                "endian" => {
+                    input.parse::<Token![=]>()?;
+                    let value = input.parse::<Ident>()?;
+                    let endian = match value.to_string().as_str() {
+                        "big" => Endian::Big,
+                        "little" => Endian::Little,
+                        _ => bail!(&value, "invalid endian {value}, expected `big` or `little`"),
+                    };
+                    if attrs.endian.replace(endian).is_some() {
+                        bail!(&keyword, "duplicate specifier for endian");
+                    }
+                }
+                _ => bail!(&keyword, "invalid specifier {keyword}"),
+            }
+
+            if input.parse::<Token![,]>().is_err() {
+                break;
+            }
+        }
+
+        if !input.is_empty() {
+            return Err(input.error("expected `,`"));
+        }
+
+        Ok(attrs)
+    }
+}
+
+/// The bits of an integer-like field: `start_bit..=end_bit` of the value held in the bytes
+/// `start_byte..=end_byte`
+struct Location {
+    start_byte: usize,
+    end_byte: usize,
+    start_bit: u32,
+    end_bit: u32,
+    endian: Endian,
+    /// A runtime offset added to the byte indices, for array elements
+    base: Option<proc_macro2::TokenStream>,
+}
+
+impl Location {
+    /// The bits `start_bit..=end_bit` of the bytes `start_byte..=end_byte`, which must fit in a
+    /// `u128`, plus a 17th byte for an unaligned 128-bit field; errors span `tokens`
+    fn new(
+        tokens: impl quote::ToTokens,
+        start_byte: usize,
+        end_byte: usize,
+        start_bit: u32,
+        end_bit: u32,
+        endian: Endian,
+    ) -> syn::Result<Self> {
+        let size = end_byte - start_byte + 1;
+        let straddles = size == 17 && (1..8).contains(&start_bit) && end_bit < start_bit + 128;
+        if size > 16 && !straddles {
+            bail!(
+                tokens,
+                "{size}-byte integers are not supported (maximum is 16 bytes, or 17 for an unaligned field of up to 128 bits)"
+            );
+        }
+        if start_bit > end_bit || end_bit as usize >= size * 8 {
+            bail!(
+                tokens,
+                "bits {start_bit}..={end_bit} must be inside the byte-range (0..={})",
+                size * 8 - 1
+            );
+        }
+
+        Ok(Self {
+            start_byte,
+            end_byte,
+            start_bit,
+            end_bit,
+            endian,
+            base: None,
+        })
+    }
+
+    /// The smallest unsigned integer that can hold every byte of the field
+    fn accumulator(&self) -> proc_macro2::TokenStream {
+        match self.accumulator_bits() {
+            32 => quote! { u32 },
+            64 => quote! { u64 },
+            _ => quote! { u128 },
+        }
+    }
+
+    fn accumulator_bits(&self) -> u32 {
+        match self.end_byte - self.start_byte + 1 {
+            0..=4 => 32,
+            5..=8 => 64,
+            // `new` caps a location at 16 bytes, besides the top byte of a 17-byte one
+            _ => 128,
+        }
+    }
+
+    /// The most significant byte of a 17-byte location, which doesn't fit in the accumulator,
+    /// and the bits of it the field covers
+    fn top_byte(&self) -> Option<(usize, u8)> {
+        if self.end_byte - self.start_byte + 1 < 17 || self.end_bit < 128 {
+            return None;
+        }
+
+        let index = match self.endian {
+            Endian::Big => self.start_byte,
+            Endian::Little => self.end_byte,
+        };
+        Some((index, !0 >> (135 - self.end_bit)))
+    }
+
+    /// The bytes held in the accumulator: all of them, or the low 16 of a 17-byte location
+    fn low_bytes(&self) -> (usize, usize) {
+        if self.end_byte - self.start_byte + 1 < 17 {
+            return (self.start_byte, self.end_byte);
+        }
+
+        match self.endian {
+            Endian::Big => (self.start_byte + 1, self.end_byte),
+            Endian::Little => (self.start_byte, self.end_byte - 1),
+        }
+    }
+
+    /// Index expression for byte `i`
+    fn index(&self) -> proc_macro2::TokenStream {
+        self.index_of(quote! { i })
+    }
+
+    /// Index expression for the byte at `i`
+    fn index_of(&self, i: impl quote::ToTokens) -> proc_macro2::TokenStream {
+        match &self.base {
+            Some(base) => quote! { #base + #i },
+            None => quote! { #i },
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.end_bit - self.start_bit + 1
+    }
+
+    /// The bits of the accumulator covered by the field, computed here so the generated code
+    /// doesn't need to worry about overflowing shifts. Bits shifted past the top of a `u128` are
+    /// in the top byte of a 17-byte location.
+    fn mask(&self) -> proc_macro2::Literal {
+        let width = self.width();
+        let mask = if width >= 128 {
+            !0_u128
+        } else {
+            (1_u128 << width) - 1
+        };
+
+        proc_macro2::Literal::u128_unsuffixed(mask << self.start_bit)
+    }
+
+    /// The non-zero bytes of `value` once placed in the field's bits, with their indices
+    fn split_bytes(&self, value: u128) -> Vec<(usize, u8)> {
+        let len = self.end_byte - self.start_byte + 1;
+        let shifted = value << self.start_bit;
+
+        (0..len)
+            .filter_map(|i| {
+                let shift = match self.endian {
+                    Endian::Big => (len - 1 - i) * 8,
+                    Endian::Little => i * 8,
+                };
+                let byte = match shift {
+                    // the top byte of a 17-byte location, where `start_bit` is non-zero
+                    128 => (value >> (128 - self.start_bit)) as u8,
+                    _ => (shifted >> shift) as u8,
+                };
+                (byte != 0).then_some((self.start_byte + i, byte))
+            })
+            .collect()
+    }
+
+    /// Reads the field's bits into `value`, shifted down to bit 0
+    fn getter_body(&self) -> proc_macro2::TokenStream {
+        self.getter_body_from(quote! { self.0 })
+    }
+
+    /// As `getter_body()`, reading from the byte slice/array `buf`
+    fn getter_body_from(&self, buf: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let acc = self.accumulator();
+        let mask = self.mask();
+        let start_bit = self.start_bit;
+        let (start_byte, end_byte) = self.low_bytes();
+        let msb_first = self.endian.msb_first(start_byte, end_byte);
+
+        let index = self.index();
+        let top = self.top_byte().map(|(top, top_mask)| {
+            let top = self.index_of(top);
+            let shift = 128 - start_bit;
+            quote! {
+                value |= ((#buf[#top] & #top_mask) as u128) << #shift;
+            }
+        });
+
+        quote! {
+            let mut value: #acc = 0;
+            for i in #msb_first {
+                value <<= 8;
+                value |= #buf[#index] as #acc;
+            }
+
+            // mask off everything outside start_bit..=end_bit
+            value = (value & #mask) >> #start_bit;
+            #top
+        }
+    }
+
+    /// Sign-extends the low `width` bits of `value` (as produced by `getter_body()`)
+    fn sign_extend(&self, width: u32) -> proc_macro2::TokenStream {
+        let signed_acc = match self.accumulator_bits() {
+            32 => quote! { i32 },
+            64 => quote! { i64 },
+            _ => quote! { i128 },
+        };
+        let shift = self.accumulator_bits() - width;
+
+        quote! {
+            let value = ((value << #shift) as #signed_acc) >> #shift;
+        }
+    }
+
+    /// As `sign_extend()`, if `ty` is signed. `signed` is `None` when only the compiler knows, e.g.
+    /// for a type alias, and then `value` goes through `i128`, which holds any value of either.
+    fn sign_extend_for(
+        &self,
+        signed: Option<bool>,
+        width: u32,
+        ty: &Type,
+    ) -> proc_macro2::TokenStream {
+        match signed {
+            Some(false) => quote! {},
+            Some(true) => self.sign_extend(width),
+            None => {
+                let sign_extend = self.sign_extend(width);
+
+                quote! {
+                    let value = if <#ty>::MIN != 0 {
+                        #sign_extend
+                        value as i128
+                    } else {
+                        value as i128
+                    };
+                }
+            }
+        }
+    }
+
+    /// As `signed_range_check()`, if `ty` is signed
+    fn range_check_for(
+        signed: Option<bool>,
+        width: u32,
+        field_name: &Ident,
+        ty: &Type,
+    ) -> proc_macro2::TokenStream {
+        match signed {
+            Some(false) => quote! {},
+            Some(true) => Self::signed_range_check(width, field_name),
+            None => {
+                let range_check = Self::signed_range_check(width, field_name);
+
+                quote! {
+                    if <#ty>::MIN != 0 {
+                        #range_check
+                    }
+                }
+            }
+        }
+    }
+
+    /// Asserts that a signed `val` fits in `width` bits as two's complement
+    fn signed_range_check(width: u32, field_name: &Ident) -> proc_macro2::TokenStream {
+        if width >= 128 {
+            return quote! {};
+        }
+
+        let max = proc_macro2::Literal::u128_unsuffixed((1_u128 << (width - 1)) - 1);
+        let min = proc_macro2::Literal::u128_unsuffixed(1_u128 << (width - 1));
+
+        quote! {
+            debug_assert!(
+                (val as i128) >= -#min && (val as i128) <= #max,
+                "value {} out of range for the {}-bit signed field `{}`",
+                val,
+                #width,
+                stringify!(#field_name),
+            );
+        }
+    }
+
+    /// Writes `val` into the field's bits, leaving the surrounding bits untouched
+    fn setter_body(&self, val: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let acc = self.accumulator();
+        let mask = self.mask();
+        let start_bit = self.start_bit;
+        let (start_byte, end_byte) = self.low_bytes();
+        let lsb_first = self.endian.lsb_first(start_byte, end_byte);
+
+        let index = self.index();
+        let top = self.top_byte().map(|(top, top_mask)| {
+            let top = self.index_of(top);
+            let shift = 128 - start_bit;
+            quote! {
+                let new = (raw >> #shift) as u8 & #top_mask;
+                self.0[#top] = self.0[#top] & !#top_mask | new;
+            }
+        });
+
+        quote! {
+            let raw = #val as #acc;
+            let mut mask: #acc = #mask;
+            let mut new = (raw << #start_bit) & mask;
+
+            for i in #lsb_first {
+                self.0[#index] = self.0[#index] & (!mask as u8) | (new as u8);
+                new >>= 8;
+                mask >>= 8;
+            }
+            #top
+        }
+    }
+}
+
+impl Endian {
+    /// Byte indices of `start..=end`, most significant byte first
+    fn msb_first(self, start: usize, end: usize) -> proc_macro2::TokenStream {
+        match self {
+            Endian::Big => quote! { #start..=#end },
+            Endian::Little => quote! { (#start..=#end).rev() },
+        }
+    }
+
+    /// Byte indices of `start..=end`, least significant byte first
+    fn lsb_first(self, start: usize, end: usize) -> proc_macro2::TokenStream {
+        match self {
+            Endian::Big => quote! { (#start..=#end).rev() },
+            Endian::Little => quote! { #start..=#end },
+        }
+    }
+
+    /// The byte in `start..=end` that holds `bit`, counting from the field's least significant bit
+    fn byte_for_bit(self, start: usize, end: usize, bit: u32) -> usize {
+        let offset = bit as usize / 8;
+        match self {
+            Endian::Big => end - offset,
+            Endian::Little => start + offset,
+        }
+    }
+}
+
 impl Parse for SingleOrRange {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let start = input.parse::<LitInt>()?.base10_parse()?;
@@ -467,8 +1810,22 @@ impl Parse for SingleOrRange {
 
         match end {
             None => Ok(Self::Single(start)),
-            Some(end) => {
-                let end = end.base10_parse()?;
+            Some(end_lit) => {
+                let end = end_lit.base10_parse()?;
+
+                if range && start >= end {
+                    bail!(end_lit, "start ({start}) must be less than end ({end})");
+                }
+                if range_incl && start > end {
+                    bail!(
+                        end_lit,
+                        "start ({start}) must not be greater than end ({end})"
+                    );
+                }
+                // so that the range's length fits in a `u32`
+                if range_incl && end == u32::MAX {
+                    bail!(end_lit, "end must be less than {end}");
+                }
 
                 Ok(if range {
                     Self::Range(start..end)
@@ -481,24 +1838,6 @@ impl Parse for SingleOrRange {
 }
 
 impl SingleOrRange {
-    fn assert_range_valid(&self, what: &str) {
-        match self {
-            SingleOrRange::Single(_) => {}
-            SingleOrRange::Range(r) => assert!(
-                r.start < r.end,
-                "start {what} ({}) must be less than end {what} ({})",
-                r.start,
-                r.end,
-            ),
-            SingleOrRange::RangeIncl(r) => assert!(
-                r.start() <= r.end(),
-                "start {what} ({}) must not be greater than end {what} ({})",
-                r.start(),
-                r.end(),
-            ),
-        }
-    }
-
     fn end_inclusive(&self) -> u32 {
         match self {
             &SingleOrRange::Single(x) => x,
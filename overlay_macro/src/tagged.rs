@@ -0,0 +1,274 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, DataEnum, Fields, Ident, LitInt, Meta, NestedMeta, Token, Visibility,
+};
+
+use crate::{combine_errors, Endian, Location, StructAttribute};
+
+struct VariantAttribute {
+    tag: LitInt,
+}
+
+/// Generates borrowed views of a tagged union: `Name<'a>`, holding `&'a` references to each
+/// variant's overlay, and `NameMut<'a>`, holding `&'a mut` references.
+pub(crate) fn overlay_enum(
+    struct_attrs: StructAttribute,
+    name: Ident,
+    vis: Visibility,
+    attrs: Vec<Attribute>,
+    data: DataEnum,
+) -> syn::Result<TokenStream> {
+    if struct_attrs.size.is_some() {
+        bail!(&name, "size is only valid for structs");
+    }
+    if struct_attrs.getter_prefix.is_some() {
+        bail!(&name, "getter_prefix is only valid for structs");
+    }
+    if struct_attrs.warn_wide_types {
+        bail!(&name, "warn_wide_types is only valid for structs");
+    }
+
+    let Some(mut tag) = struct_attrs.tag else {
+        bail!(
+            &name,
+            "enums must give the location of their tag, e.g. #[overlay(tag(byte = 0))]"
+        );
+    };
+    tag.renumber_bits(struct_attrs.bit_order);
+
+    if tag.nested
+        || tag.tail
+        || tag.len.is_some()
+        || tag.stride.is_some()
+        || tag.element_bits.is_some()
+        || tag.with.is_some()
+        || tag.unknown.is_some()
+        || tag.magic.is_some()
+        || tag.reserved
+        || tag.checksum.is_some()
+        || tag.getter.is_some()
+        || tag.setter.is_some()
+        || tag.kind.is_some()
+    {
+        bail!(
+            &name,
+            "a tag can only have a byte range, bit range and endian"
+        );
+    }
+
+    let Some(byte_range) = &tag.byte else {
+        bail!(&name, "a tag needs a byte range");
+    };
+    if byte_range.len() > 16 {
+        bail!(&name, "a tag can be at most 16 bytes");
+    }
+
+    let lim = byte_range.len() * 8 - 1;
+    let (start_bit, end_bit) = match &tag.bits {
+        None => (0, lim),
+        Some(bits) => (bits.start(), bits.end_inclusive()),
+    };
+    if end_bit > lim {
+        bail!(
+            &name,
+            "tag bits ({start_bit}..={end_bit}) must be inside the byte-range (0..={lim})"
+        );
+    }
+
+    let location = Location::new(
+        &name,
+        byte_range.start() as usize,
+        byte_range.end_inclusive() as usize,
+        start_bit,
+        end_bit,
+        tag.endian.or(struct_attrs.endian).unwrap_or(Endian::Big),
+    )?;
+    let read_tag = location.getter_body_from(quote! { bytes });
+    let tag_len = location.end_byte + 1;
+
+    let mut ref_variants = vec![];
+    let mut mut_variants = vec![];
+    let mut ref_arms = vec![];
+    let mut mut_arms = vec![];
+    // `(variant, tag)` of each variant so far, checked for duplicates
+    let mut tags: Vec<(Ident, u128)> = vec![];
+    // reported together, once every variant has been seen
+    let mut errors = vec![];
+    for variant in data.variants {
+        let variant_name = &variant.ident;
+
+        let mut found = false;
+        let mut variant_tag = None;
+        let mut variant_attrs = vec![];
+        for attr in variant.attrs {
+            if attr.path.is_ident("overlay") {
+                found = true;
+
+                match attr.parse_args::<VariantAttribute>() {
+                    Ok(parsed) => {
+                        if variant_tag.replace(parsed.tag).is_some() {
+                            errors.push(syn::Error::new_spanned(
+                                &attr,
+                                format!("duplicate #[overlay(...)] attribute for {variant_name}"),
+                            ));
+                        }
+                    }
+                    Err(error) => errors.push(error),
+                }
+            } else {
+                variant_attrs.push(attr);
+            }
+        }
+        let Some(variant_tag) = variant_tag else {
+            // an attribute that didn't parse has already been reported
+            if !found {
+                errors.push(syn::Error::new(
+                    variant_name.span(),
+                    format!("No #[overlay(tag = ...)] attribute found for {variant_name}"),
+                ));
+            }
+            continue;
+        };
+
+        // tags are matched against the value as read, so must fit in its bits and be distinct
+        let value = match variant_tag.base10_parse::<u128>() {
+            Ok(value) => value,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        let width = location.width();
+        if width < 128 && value >> width != 0 {
+            errors.push(syn::Error::new_spanned(
+                &variant_tag,
+                format!("tag {variant_tag} of {variant_name} doesn't fit in the {width} bits of the tag"),
+            ));
+            continue;
+        }
+        if let Some((other, _)) = tags.iter().find(|(_, other)| *other == value) {
+            errors.push(syn::Error::new_spanned(
+                &variant_tag,
+                format!("tag {variant_tag} of {variant_name} is already the tag of {other}"),
+            ));
+            continue;
+        }
+        tags.push((variant_name.clone(), value));
+
+        match variant.fields {
+            Fields::Unit => {
+                ref_variants.push(quote! { #(#variant_attrs)* #variant_name });
+                mut_variants.push(quote! { #(#variant_attrs)* #variant_name });
+                ref_arms.push(quote! { #variant_tag => Self::#variant_name });
+                mut_arms.push(quote! { #variant_tag => Self::#variant_name });
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+
+                ref_variants.push(quote! { #(#variant_attrs)* #variant_name(&'a #ty) });
+                mut_variants.push(quote! { #(#variant_attrs)* #variant_name(&'a mut #ty) });
+                ref_arms.push(quote! {
+                    #variant_tag => Self::#variant_name(
+                        overlay::Overlay::overlay(&bytes[#tag_len..])?
+                    )
+                });
+                mut_arms.push(quote! {
+                    #variant_tag => Self::#variant_name(
+                        overlay::Overlay::overlay_mut(&mut bytes[#tag_len..])?
+                    )
+                });
+            }
+            fields => errors.push(syn::Error::new_spanned(
+                fields,
+                "enum variants must be units or have a single unnamed field",
+            )),
+        }
+    }
+    combine_errors(errors)?;
+
+    // `&mut` references can't be cloned or copied, so only carry `Debug` over to the mutable view
+    let mut mut_attrs = vec![];
+    for attr in &attrs {
+        match attr.parse_meta() {
+            Ok(Meta::List(meta_list)) if meta_list.path.is_ident("derive") => {
+                let debug = meta_list.nested.iter().any(|nested| {
+                    matches!(nested, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("Debug"))
+                });
+                if debug {
+                    mut_attrs.push(quote! { #[derive(Debug)] });
+                }
+            }
+            _ => mut_attrs.push(quote! { #attr }),
+        }
+    }
+
+    let mut_name = format_ident!("{}Mut", name);
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis enum #name<'a> {
+            #(#ref_variants,)*
+        }
+
+        #(#mut_attrs)*
+        #vis enum #mut_name<'a> {
+            #(#mut_variants,)*
+        }
+
+        impl<'a> #name<'a> {
+            /// The number of bytes up to and including the tag. Variants are overlaid on the
+            /// bytes that follow.
+            pub const TAG_LEN: usize = #tag_len;
+
+            pub fn overlay(bytes: &'a [u8]) -> core::result::Result<Self, overlay::Error> {
+                if bytes.len() < #tag_len {
+                    return Err(overlay::Error::InsufficientLength);
+                }
+
+                #read_tag
+
+                Ok(match value {
+                    #(#ref_arms,)*
+                    _ => return Err(overlay::Error::UnknownTag),
+                })
+            }
+        }
+
+        impl<'a> #mut_name<'a> {
+            pub fn overlay_mut(bytes: &'a mut [u8]) -> core::result::Result<Self, overlay::Error> {
+                if bytes.len() < #tag_len {
+                    return Err(overlay::Error::InsufficientLength);
+                }
+
+                #read_tag
+
+                Ok(match value {
+                    #(#mut_arms,)*
+                    _ => return Err(overlay::Error::UnknownTag),
+                })
+            }
+        }
+    })
+}
+
+impl Parse for VariantAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword = input.parse::<Ident>()?;
+        if keyword != "tag" {
+            bail!(&keyword, "invalid specifier {keyword}, expected `tag`");
+        }
+        input.parse::<Token![=]>()?;
+
+        let tag = input.parse()?;
+
+        // permit a trailing comma
+        let _ = input.parse::<Token![,]>();
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens"));
+        }
+
+        Ok(Self { tag })
+    }
+}
@@ -1,5 +1,5 @@
 use overlay::Overlay;
-use overlay_macro::overlay;
+use overlay_macro::{overlay, overlay_enum};
 
 #[overlay]
 #[derive(Clone, Debug, Default)]
@@ -25,6 +25,7 @@ pub struct NoDebug {
 }
 
 #[test]
+#[allow(clippy::unnecessary_mut_passed, clippy::bool_assert_comparison)]
 fn integer_bool_getters() {
     let mut bytes = [
         5_u8,
@@ -291,10 +292,10 @@ fn awkward_offsets_and_overlap() {
         #[overlay(bytes=0..=1, bits=3..14)]
         a: u16,
 
-        #[overlay(bytes=0..=1, bits=9..=15)]
+        #[overlay(bytes=0..=1, bits=9..=15, alias)]
         b: u16,
 
-        #[overlay(bytes=0..4, bits=5..=27)]
+        #[overlay(bytes=0..4, bits=5..=27, alias)]
         c: u32,
     }
 
@@ -416,3 +417,1796 @@ fn nested_struct() {
 
     assert_eq!(bytes, [187, (65439_u16 >> 8) as u8, 65439_u16 as u8, 253]);
 }
+
+#[test]
+fn little_endian() {
+    #[overlay(endian = little)]
+    #[derive(Debug)]
+    struct DeviceDescriptor {
+        #[overlay(byte = 0)]
+        length: u8,
+
+        #[overlay(bytes=2..=3)]
+        bcd_usb: u16,
+
+        #[overlay(bytes=4..=7, bits=4..28)]
+        middle: u32,
+
+        #[overlay(bytes=8..=9, be)]
+        big: u16,
+
+        #[overlay(bytes=10..=11, bit=9)]
+        flag: bool,
+    }
+
+    let mut bytes = [
+        18, 0, 0x00, 0x02, 0x21, 0x43, 0x65, 0x87, 0x12, 0x34, 0, 0b10,
+    ];
+    let desc = DeviceDescriptor::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(desc.length(), 18);
+    assert_eq!(desc.bcd_usb(), 0x0200);
+    assert_eq!(desc.middle(), 0x76_5432);
+    assert_eq!(desc.big(), 0x1234);
+    assert!(desc.flag());
+
+    desc.set_bcd_usb(0x0110);
+    desc.set_middle(0xab_cdef);
+    desc.set_big(0x5678);
+    desc.set_flag(false);
+    assert_eq!(
+        desc.as_bytes(),
+        &[18, 0, 0x10, 0x01, 0xf1, 0xde, 0xbc, 0x8a, 0x56, 0x78, 0, 0]
+    );
+
+    assert_eq!(
+        format!("{desc:?}"),
+        "DeviceDescriptor { length: 18, bcd_usb: 272, middle: 11259375, big: 22136, flag: false }"
+    );
+}
+
+#[test]
+fn little_endian_fields() {
+    #[derive(Debug, Eq, PartialEq)]
+    enum E {
+        A = 0x100,
+        B = 0x200,
+    }
+
+    impl TryFrom<u16> for E {
+        type Error = ();
+
+        fn try_from(v: u16) -> Result<Self, Self::Error> {
+            Ok(match v {
+                0x100 => Self::A,
+                0x200 => Self::B,
+                _ => return Err(()),
+            })
+        }
+    }
+
+    #[overlay]
+    struct Mixed {
+        #[overlay(bytes=0..=1, le)]
+        e: E,
+
+        #[overlay(bytes=2..=3)]
+        big: u16,
+
+        #[overlay(bytes=4..=5, le)]
+        little: u16,
+    }
+
+    let mut bytes = [0, 1, 1, 2, 1, 2];
+    let mixed = Mixed::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(mixed.e(), Ok(E::A));
+    assert_eq!(mixed.big(), 0x0102);
+    assert_eq!(mixed.little(), 0x0201);
+
+    mixed.set_e(E::B);
+    assert_eq!(mixed.as_bytes(), &[0, 2, 1, 2, 1, 2]);
+}
+
+#[test]
+fn wide_integers() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Read16 {
+        #[overlay(byte = 0)]
+        op_code: u8,
+
+        #[overlay(bytes=2..=9)]
+        lba: u64,
+
+        #[overlay(bytes=10..=13)]
+        transfer_length: u32,
+
+        #[overlay(bytes=2..=13, bits=0..=95, alias)]
+        combined: u128,
+
+        #[overlay(bytes=0..=15, bits=4..=67, alias)]
+        straddle: u64,
+
+        #[overlay(bytes=0..=15, alias)]
+        whole: u128,
+    }
+
+    let mut bytes = [0; 16];
+    let cmd = Read16::overlay_mut(&mut bytes).unwrap();
+
+    cmd.set_op_code(0x88);
+    cmd.set_lba(0xfedc_ba98_7654_3210);
+    cmd.set_transfer_length(0x8000_0001);
+
+    assert_eq!(cmd.lba(), 0xfedc_ba98_7654_3210);
+    assert_eq!(cmd.transfer_length(), 0x8000_0001);
+    assert_eq!(cmd.combined(), 0xfedc_ba98_7654_3210_8000_0001);
+    assert_eq!(
+        cmd.as_bytes(),
+        &[0x88, 0, 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x80, 0, 0, 0x01, 0, 0]
+    );
+
+    cmd.set_whole(u128::MAX);
+    cmd.set_straddle(0);
+    assert_eq!(cmd.whole(), !(u64::MAX as u128) << 4 | 0xf);
+    cmd.set_straddle(0x1234_5678_9abc_def0);
+    assert_eq!(cmd.straddle(), 0x1234_5678_9abc_def0);
+    assert_eq!(cmd.whole() >> 68, u128::MAX >> 68);
+    assert_eq!(cmd.whole() & 0xf, 0xf);
+}
+
+#[test]
+fn single_bit_integer() {
+    #[overlay]
+    struct Bits {
+        #[overlay(byte=0, bits=0..=0)]
+        low: u8,
+
+        #[overlay(byte=0, bits=1..=7)]
+        high: u8,
+    }
+
+    let mut bytes = [0xff];
+    let bits = Bits::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(bits.low(), 1);
+    assert_eq!(bits.high(), 0x7f);
+
+    bits.set_low(0);
+    assert_eq!(bits.as_bytes(), &[0xfe]);
+    assert_eq!(bits.high(), 0x7f);
+}
+
+#[test]
+fn wide_enum() {
+    #[derive(Debug, Eq, PartialEq)]
+    #[repr(u64)]
+    enum Big {
+        Small = 1,
+        Large = 0x1_0000_0000,
+    }
+
+    impl TryFrom<u64> for Big {
+        type Error = u64;
+
+        fn try_from(v: u64) -> Result<Self, Self::Error> {
+            Ok(match v {
+                1 => Self::Small,
+                0x1_0000_0000 => Self::Large,
+                _ => return Err(v),
+            })
+        }
+    }
+
+    #[overlay]
+    struct Abc {
+        #[overlay(bytes=0..8)]
+        e: Big,
+    }
+
+    let mut bytes = [0, 0, 0, 1, 0, 0, 0, 0];
+    let abc = Abc::overlay_mut(&mut bytes).unwrap();
+    assert_eq!(abc.e(), Ok(Big::Large));
+
+    abc.set_e(Big::Small);
+    assert_eq!(abc.as_bytes(), &[0, 0, 0, 0, 0, 0, 0, 1]);
+
+    abc.as_bytes_mut()[0] = 0x80;
+    assert_eq!(abc.e(), Err(0x8000_0000_0000_0001));
+}
+
+#[overlay]
+struct Signed {
+    #[overlay(bytes=0..=1, bits=4..16)]
+    temperature: i16,
+
+    #[overlay(byte = 2)]
+    full: i8,
+
+    #[overlay(bytes=3..=7)]
+    wide: i64,
+
+    #[overlay(byte=8, bits=0..=0)]
+    one: i8,
+}
+
+#[test]
+fn signed_getters_setters() {
+    let mut bytes = [0xff, 0x6f, 0x80, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x01];
+    let s = Signed::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(s.temperature(), -10);
+    assert_eq!(s.full(), -128);
+    assert_eq!(s.wide(), -2);
+    assert_eq!(s.one(), -1);
+
+    s.set_temperature(-2048);
+    assert_eq!(s.as_bytes()[0..2], [0x80, 0x0f]);
+    assert_eq!(s.temperature(), -2048);
+
+    s.set_temperature(2047);
+    assert_eq!(s.as_bytes()[0..2], [0x7f, 0xff]);
+    assert_eq!(s.temperature(), 2047);
+
+    s.set_wide(-0x80_0000_0000);
+    assert_eq!(s.as_bytes()[3..8], [0x80, 0, 0, 0, 0]);
+    assert_eq!(s.wide(), -0x80_0000_0000);
+
+    s.set_full(5);
+    s.set_one(0);
+    assert_eq!(s.full(), 5);
+    assert_eq!(s.one(), 0);
+    assert_eq!(s.as_bytes()[8], 0);
+}
+
+#[test]
+#[should_panic(expected = "out of range for the 12-bit signed field `temperature`")]
+fn signed_out_of_range() {
+    let mut s = Signed::new();
+    s.set_temperature(2048);
+}
+
+#[test]
+fn integer_and_enum_arrays() {
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    enum Dir {
+        Out = 0,
+        In = 0x80,
+    }
+
+    impl TryFrom<u8> for Dir {
+        type Error = u8;
+
+        fn try_from(v: u8) -> Result<Self, Self::Error> {
+            Ok(match v {
+                0 => Self::Out,
+                0x80 => Self::In,
+                _ => return Err(v),
+            })
+        }
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Table {
+        #[overlay(bytes=0..8)] // stride inferred as 2
+        words: [u16; 4],
+
+        #[overlay(bytes=8..12, stride=2, le)]
+        padded: [i8; 2],
+
+        #[overlay(bytes=12..=13)]
+        dirs: [Dir; 2],
+
+        #[overlay(bytes=14..=15, stride=1)]
+        raw: [u8; 2],
+    }
+
+    let mut bytes = [
+        0x12, 0x34, 0, 1, 0xff, 0xff, 0, 0, 0xfe, 0, 0xff, 0x7f, 0x80, 3, 7, 9,
+    ];
+    let table = Table::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(table.words_len(), 4);
+    assert_eq!(table.words(0), 0x1234);
+    assert_eq!(table.words(2), 0xffff);
+    assert_eq!(
+        table.words_iter().collect::<Vec<_>>(),
+        [0x1234, 1, 0xffff, 0]
+    );
+    assert_eq!(table.padded(0), -2);
+    assert_eq!(table.padded(1), -1); // the second byte is padding
+    assert_eq!(table.dirs(0), Ok(Dir::In));
+    assert_eq!(table.dirs(1), Err(3));
+    assert_eq!(table.raw(1), 9);
+
+    table.set_words(3, 0xabcd);
+    table.set_padded(1, 5); // leaving the padding alone
+    table.set_dirs(1, Dir::Out);
+    table.set_raw(0, 1);
+    assert_eq!(
+        table.as_bytes(),
+        &[0x12, 0x34, 0, 1, 0xff, 0xff, 0xab, 0xcd, 0xfe, 0, 5, 0x7f, 0x80, 0, 1, 9]
+    );
+
+    assert_eq!(
+        format!("{table:?}"),
+        "Table { words: [4660, 1, 65535, 43981], padded: [-2, 5], dirs: [Ok(In), Ok(Out)], raw: [1, 9] }"
+    );
+
+    // elements are sized by their type, so a wider stride adds padding
+    #[overlay]
+    struct Spaced {
+        #[overlay(bytes=0..8, stride=4)]
+        words: [u16; 2],
+
+        #[overlay(bytes=8..18, stride=6, le)]
+        floats: [f32; 2],
+    }
+
+    let mut bytes = [0; 18];
+    bytes[..8].copy_from_slice(&[0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]);
+    let spaced = Spaced::overlay_mut(&mut bytes).unwrap();
+    assert_eq!(spaced.words(0), 0x1122);
+    assert_eq!(spaced.words(1), 0x5566);
+
+    spaced.set_words(0, 0xabcd);
+    spaced.set_floats(1, 1.5);
+    assert_eq!(spaced.floats(1), 1.5);
+    assert_eq!(
+        spaced.as_bytes(),
+        &[0xab, 0xcd, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0, 0, 0, 0, 0, 0, 0, 0, 0xc0, 0x3f]
+    );
+}
+
+#[test]
+#[should_panic(expected = "index 4 out of range for `words` (length 4)")]
+fn array_index_out_of_range() {
+    #[overlay]
+    struct Table {
+        #[overlay(bytes=0..8)]
+        words: [u16; 4],
+
+        #[overlay(byte = 8)]
+        after: u8,
+    }
+
+    let table = Table::new();
+    let _ = table.after();
+    table.words(4);
+}
+
+#[test]
+fn nested_arrays() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Endpoint {
+        #[overlay(byte = 0)]
+        address: u8,
+
+        #[overlay(bytes=1..=2)]
+        max_packet: u16,
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Interface {
+        #[overlay(byte = 0)]
+        count: u8,
+
+        #[overlay(bytes=1..=8, stride=4, nested)] // one byte of padding per endpoint
+        endpoints: [Endpoint; 2],
+    }
+
+    let mut bytes = [2, 0x81, 0, 64, 0xee, 0x02, 2, 0, 0xee];
+    let iface = Interface::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(iface.endpoints_len(), 2);
+    assert_eq!(iface.endpoints(0).address(), 0x81);
+    assert_eq!(iface.endpoints(1).max_packet(), 512);
+    assert_eq!(
+        iface
+            .endpoints_iter()
+            .map(Endpoint::address)
+            .collect::<Vec<_>>(),
+        [0x81, 2]
+    );
+
+    iface.endpoints_mut(1).set_address(3);
+    assert_eq!(iface.as_bytes(), &[2, 0x81, 0, 64, 0xee, 3, 2, 0, 0xee]);
+
+    assert_eq!(
+        format!("{iface:?}"),
+        "Interface { count: 2, endpoints: [Endpoint { address: 129, max_packet: 64 }, Endpoint { address: 3, max_packet: 512 }] }"
+    );
+}
+
+#[test]
+fn bit_packed_arrays() {
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    enum Mode {
+        Input,
+        Output,
+        Alternate,
+        Analog,
+    }
+
+    impl TryFrom<u8> for Mode {
+        type Error = ();
+
+        fn try_from(v: u8) -> Result<Self, Self::Error> {
+            Ok(match v {
+                0 => Self::Input,
+                1 => Self::Output,
+                2 => Self::Alternate,
+                3 => Self::Analog,
+                _ => return Err(()),
+            })
+        }
+    }
+
+    #[overlay(endian = little)]
+    #[derive(Debug)]
+    struct Gpio {
+        #[overlay(bytes=0..4, element_bits=2)]
+        modes: [Mode; 16],
+
+        #[overlay(bytes=4..8, element_bits=4)]
+        priorities: [u8; 8],
+
+        #[overlay(bytes=8..=9, bits=4..=11, element_bits=4)]
+        offsets: [i8; 2],
+
+        #[overlay(bytes=10..=11)]
+        flags: [bool; 16],
+    }
+
+    let mut bytes = [
+        0b11_10_01_00,
+        0,
+        0,
+        0b01_00_00_00,
+        0x21,
+        0x43,
+        0x65,
+        0x87,
+        0xf0,
+        0x07,
+        0b1000_0001,
+        0b0000_0010,
+    ];
+    let gpio = Gpio::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(gpio.modes_len(), 16);
+    assert_eq!(gpio.modes(0), Ok(Mode::Input));
+    assert_eq!(gpio.modes(1), Ok(Mode::Output));
+    assert_eq!(gpio.modes(2), Ok(Mode::Alternate));
+    assert_eq!(gpio.modes(3), Ok(Mode::Analog));
+    assert_eq!(gpio.modes(15), Ok(Mode::Output));
+    assert_eq!(
+        gpio.priorities_iter().collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5, 6, 7, 8]
+    );
+    assert_eq!(gpio.offsets(0), -1);
+    assert_eq!(gpio.offsets(1), 7);
+    assert!(gpio.flags(0));
+    assert!(!gpio.flags(1));
+    assert!(gpio.flags(7));
+    assert!(gpio.flags(9));
+
+    gpio.set_modes(4, Mode::Analog);
+    gpio.set_modes(15, Mode::Input);
+    gpio.set_priorities(7, 0xf);
+    gpio.set_offsets(1, -8);
+    gpio.set_flags(0, false);
+    gpio.set_flags(15, true);
+    assert_eq!(
+        gpio.as_bytes(),
+        &[
+            0b11_10_01_00,
+            0b11,
+            0,
+            0,
+            0x21,
+            0x43,
+            0x65,
+            0xf7,
+            0xf0,
+            0x08,
+            0b1000_0000,
+            0b1000_0010
+        ]
+    );
+
+    assert_eq!(
+        format!("{:?}", Gpio::new()),
+        "Gpio { modes: [Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input), Ok(Input)], priorities: [0, 0, 0, 0, 0, 0, 0, 0], offsets: [0, 0], flags: [false, false, false, false, false, false, false, false, false, false, false, false, false, false, false, false] }"
+    );
+}
+
+#[test]
+fn tail() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Packet {
+        #[overlay(byte = 0)]
+        kind: u8,
+
+        #[overlay(bytes=1..=2)]
+        length: u16,
+
+        #[overlay(tail)]
+        payload: [u8],
+    }
+
+    assert_eq!(Packet::BYTE_LEN, 3);
+
+    let mut bytes = [7, 0, 4, 1, 2, 3, 4];
+    let packet = Packet::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(packet.kind(), 7);
+    assert_eq!(packet.length(), 4);
+    assert_eq!(packet.payload(), &[1, 2, 3, 4]);
+    assert_eq!(packet.as_bytes().len(), 7);
+
+    packet.payload_mut()[3] = 9;
+    packet.set_length(3);
+    assert_eq!(packet.as_bytes(), &[7, 0, 3, 1, 2, 3, 9]);
+    assert_eq!(
+        format!("{packet:?}"),
+        "Packet { kind: 7, length: 3, payload: [1, 2, 3, 9] }"
+    );
+
+    let header_only = Packet::overlay(&bytes[..3]).unwrap();
+    assert!(header_only.payload().is_empty());
+    assert!(Packet::overlay(&bytes[..2]).is_err());
+}
+
+#[test]
+fn len_fields() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Endpoint {
+        #[overlay(byte = 0)]
+        address: u8,
+
+        #[overlay(byte = 1)]
+        attributes: u8,
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Interface {
+        #[overlay(byte = 0)]
+        num_endpoints: u8,
+
+        #[overlay(byte = 1)]
+        extra_length: u8,
+
+        #[overlay(len = num_endpoints)]
+        endpoints: [Endpoint],
+
+        #[overlay(len = extra_length)]
+        extra: [u8],
+    }
+
+    assert_eq!(Interface::BYTE_LEN, 2);
+
+    let mut bytes = [2, 3, 0x81, 3, 0x02, 2, 7, 8, 9, 0xff];
+    let iface = Interface::overlay_mut(&mut bytes).unwrap();
+
+    let endpoints = iface.endpoints().unwrap();
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(endpoints[0].address(), 0x81);
+    assert_eq!(endpoints[1].attributes(), 2);
+    assert_eq!(iface.extra(), Ok(&[7, 8, 9][..]));
+
+    iface.endpoints_mut().unwrap()[1].set_address(0x03);
+    iface.extra_mut().unwrap()[0] = 6;
+    assert_eq!(iface.as_bytes(), &[2, 3, 0x81, 3, 0x03, 2, 6, 8, 9, 0xff]);
+
+    assert_eq!(
+        format!("{iface:?}"),
+        "Interface { num_endpoints: 2, extra_length: 3, endpoints: Ok([Endpoint { address: 129, attributes: 3 }, Endpoint { address: 3, attributes: 2 }]), extra: Ok([6, 8, 9]) }"
+    );
+
+    // the extra bytes run past the end of the buffer
+    iface.set_extra_length(5);
+    assert_eq!(iface.extra(), Err(overlay::Error::InsufficientLength));
+    assert!(iface.endpoints().is_ok());
+
+    iface.set_num_endpoints(5);
+    assert_eq!(
+        iface.endpoints().map(|eps| eps.len()),
+        Err(overlay::Error::InsufficientLength)
+    );
+
+    // counts from untrusted data can overflow the size calculations
+    #[overlay]
+    struct Huge {
+        #[overlay(bytes = 0..=7)]
+        count: u64,
+
+        #[overlay(bytes = 8..=15)]
+        len: u64,
+
+        #[overlay(len = count)]
+        records: [Endpoint],
+
+        #[overlay(len = len)]
+        data: [u8],
+    }
+
+    let mut bytes = [0; 20];
+    let huge = Huge::overlay_mut(&mut bytes).unwrap();
+    huge.set_count((1 << 63) + 1);
+    assert_eq!(
+        huge.records().map(|records| records.len()),
+        Err(overlay::Error::InsufficientLength)
+    );
+    assert_eq!(
+        huge.records_mut().map(|records| records.len()),
+        Err(overlay::Error::InsufficientLength)
+    );
+    assert_eq!(huge.data(), Err(overlay::Error::InsufficientLength));
+
+    huge.set_count(1);
+    huge.set_len(u64::MAX);
+    assert!(huge.records().is_ok());
+    assert_eq!(huge.data(), Err(overlay::Error::InsufficientLength));
+    assert_eq!(huge.data_mut(), Err(overlay::Error::InsufficientLength));
+}
+
+#[test]
+fn tagged_union() {
+    #[overlay]
+    #[derive(Debug)]
+    pub struct Echo {
+        #[overlay(byte = 0)]
+        code: u8,
+
+        #[overlay(bytes=1..=2)]
+        checksum: u16,
+
+        #[overlay(bytes=3..=4)]
+        identifier: u16,
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    pub struct Unreachable {
+        #[overlay(byte = 0)]
+        code: u8,
+
+        #[overlay(tail)]
+        original: [u8],
+    }
+
+    #[overlay(tag(byte = 0))]
+    #[derive(Debug)]
+    pub enum Icmp {
+        #[overlay(tag = 0)]
+        EchoReply(Echo),
+
+        #[overlay(tag = 3)]
+        Unreachable(Unreachable),
+
+        #[overlay(tag = 8)]
+        EchoRequest(Echo),
+
+        #[overlay(tag = 0x7f)]
+        Empty,
+    }
+
+    assert_eq!(Icmp::TAG_LEN, 1);
+
+    let mut bytes = [8, 0, 0x12, 0x34, 0, 1];
+    match Icmp::overlay(&bytes) {
+        Ok(Icmp::EchoRequest(echo)) => {
+            assert_eq!(echo.checksum(), 0x1234);
+            assert_eq!(echo.identifier(), 1);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+
+    match IcmpMut::overlay_mut(&mut bytes) {
+        Ok(IcmpMut::EchoRequest(echo)) => echo.set_identifier(2),
+        other => panic!("unexpected {other:?}"),
+    }
+    assert_eq!(bytes, [8, 0, 0x12, 0x34, 0, 2]);
+
+    let bytes = [3, 1, 0x45, 0];
+    match Icmp::overlay(&bytes) {
+        Ok(Icmp::Unreachable(u)) => {
+            assert_eq!(u.code(), 1);
+            assert_eq!(u.original(), &[0x45, 0]);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+
+    assert!(matches!(Icmp::overlay(&[0x7f]), Ok(Icmp::Empty)));
+    assert_eq!(
+        Icmp::overlay(&[0, 0]).unwrap_err(),
+        overlay::Error::InsufficientLength
+    );
+    assert_eq!(
+        Icmp::overlay(&[]).unwrap_err(),
+        overlay::Error::InsufficientLength
+    );
+    assert_eq!(
+        Icmp::overlay(&[9, 0, 0, 0, 0, 0]).unwrap_err(),
+        overlay::Error::UnknownTag
+    );
+}
+
+#[test]
+fn tagged_union_bits() {
+    #[overlay]
+    #[derive(Debug)]
+    pub struct V4 {
+        #[overlay(byte = 0)]
+        ttl: u8,
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    pub struct V6 {
+        #[overlay(byte = 0)]
+        hop_limit: u8,
+    }
+
+    #[overlay(tag(byte = 0, bits = 4..8))]
+    #[derive(Debug)]
+    pub enum Ip {
+        #[overlay(tag = 4)]
+        V4(V4),
+
+        #[overlay(tag = 6)]
+        V6(V6),
+    }
+
+    assert!(matches!(Ip::overlay(&[0x45, 64]), Ok(Ip::V4(v4)) if v4.ttl() == 64));
+    assert!(matches!(Ip::overlay(&[0x60, 32]), Ok(Ip::V6(v6)) if v6.hop_limit() == 32));
+}
+
+#[test]
+fn floats() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Telemetry {
+        #[overlay(bytes=0..4)]
+        temperature: f32,
+
+        #[overlay(bytes=4..12, le)]
+        pressure: f64,
+
+        #[overlay(bytes=12..20, le)]
+        axes: [f32; 2],
+    }
+
+    let mut bytes = [0; 20];
+    bytes[0..4].copy_from_slice(&21.5_f32.to_be_bytes());
+    bytes[4..12].copy_from_slice(&1013.25_f64.to_le_bytes());
+    bytes[12..16].copy_from_slice(&(-1.0_f32).to_le_bytes());
+    let t = Telemetry::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(t.temperature(), 21.5);
+    assert_eq!(t.pressure(), 1013.25);
+    assert_eq!(t.axes(0), -1.0);
+    assert_eq!(t.axes(1), 0.0);
+
+    t.set_temperature(-40.125);
+    t.set_pressure(f64::MAX);
+    t.set_axes(1, 0.5);
+    assert_eq!(t.as_bytes()[0..4], (-40.125_f32).to_be_bytes());
+    assert_eq!(t.as_bytes()[4..12], f64::MAX.to_le_bytes());
+    assert_eq!(t.as_bytes()[16..20], 0.5_f32.to_le_bytes());
+
+    assert_eq!(
+        format!("{t:?}"),
+        format!(
+            "Telemetry {{ temperature: -40.125, pressure: {:?}, axes: [-1.0, 0.5] }}",
+            f64::MAX
+        )
+    );
+}
+
+#[test]
+fn with_conversion() {
+    use core::num::NonZeroU16;
+    use core::time::Duration;
+
+    mod millis {
+        use core::time::Duration;
+
+        pub fn decode(raw: u32) -> Duration {
+            Duration::from_millis(raw.into())
+        }
+
+        pub fn encode(d: Duration) -> u32 {
+            d.as_millis() as u32
+        }
+    }
+
+    mod non_zero {
+        use core::num::NonZeroU16;
+
+        pub fn decode(raw: u16) -> Option<NonZeroU16> {
+            NonZeroU16::new(raw)
+        }
+
+        pub fn encode(v: Option<NonZeroU16>) -> u16 {
+            v.map_or(0, NonZeroU16::get)
+        }
+    }
+
+    mod tenths {
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct Celsius(pub f32);
+
+        pub fn decode(raw: u16) -> Celsius {
+            Celsius(raw as f32 / 10.0)
+        }
+
+        pub fn encode(c: Celsius) -> u16 {
+            (c.0 * 10.0) as u16
+        }
+    }
+    use tenths::Celsius;
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Config {
+        #[overlay(bytes=0..4, with = millis)]
+        timeout: Duration,
+
+        #[overlay(bytes=4..6, with = non_zero)]
+        id: Option<NonZeroU16>,
+
+        #[overlay(bytes=6..8, bits=0..12, with = tenths)]
+        limit: Celsius,
+
+        #[overlay(bytes=8..11, with = millis)]
+        interval: Duration,
+    }
+
+    let mut bytes = [0, 0, 0x01, 0xf4, 0, 0, 0xf0, 0xfa, 0x01, 0x00, 0x00];
+    let config = Config::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(config.timeout(), Duration::from_millis(500));
+    assert_eq!(config.id(), None);
+    assert_eq!(config.limit(), Celsius(25.0));
+    assert_eq!(config.interval(), Duration::from_millis(0x01_0000));
+
+    config.set_timeout(Duration::from_secs(2));
+    config.set_id(NonZeroU16::new(0x1234));
+    config.set_limit(Celsius(100.0));
+    config.set_interval(Duration::from_millis(0x12_3456));
+    assert_eq!(
+        config.as_bytes(),
+        &[0, 0, 0x07, 0xd0, 0x12, 0x34, 0xf3, 0xe8, 0x12, 0x34, 0x56]
+    );
+
+    assert_eq!(
+        format!("{config:?}"),
+        "Config { timeout: 2s, id: Some(4660), limit: Celsius(100.0), interval: 1193.046s }"
+    );
+}
+
+#[test]
+fn unknown_enum_values() {
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    enum Class {
+        Audio,
+        Hid,
+        Other(u8),
+    }
+
+    impl TryFrom<u8> for Class {
+        type Error = ();
+
+        fn try_from(v: u8) -> Result<Self, Self::Error> {
+            Ok(match v {
+                1 => Self::Audio,
+                3 => Self::Hid,
+                _ => return Err(()),
+            })
+        }
+    }
+
+    impl From<Class> for u8 {
+        fn from(c: Class) -> u8 {
+            match c {
+                Class::Audio => 1,
+                Class::Hid => 3,
+                Class::Other(raw) => raw,
+            }
+        }
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Interface {
+        #[overlay(byte = 0, unknown = Other)]
+        class: Class,
+
+        #[overlay(byte=1, bits=4..8, unknown = Other)]
+        sub_class: Class,
+    }
+
+    let mut bytes = [0xff, 0x3a];
+    let iface = Interface::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(iface.class(), Class::Other(0xff));
+    assert_eq!(iface.sub_class(), Class::Hid);
+    assert_eq!(
+        format!("{iface:?}"),
+        "Interface { class: Other(255), sub_class: Hid }"
+    );
+
+    iface.set_class(Class::Audio);
+    iface.set_sub_class(Class::Other(9));
+    assert_eq!(iface.as_bytes(), &[1, 0x9a]);
+
+    // the raw value is written back unchanged
+    iface.set_class(Class::Other(0xfe));
+    iface.set_class(iface.class());
+    assert_eq!(iface.as_bytes(), &[0xfe, 0x9a]);
+}
+
+#[test]
+fn overlay_enum_conversions() {
+    #[overlay_enum]
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    enum Speed {
+        Low = 1,
+        Full,
+        High = 0x300,
+    }
+
+    #[overlay_enum]
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    enum Class {
+        Audio = 1,
+        Hid = 3,
+        #[overlay_enum(range = 0xf0..=0xfe)]
+        Vendor(u8),
+        #[overlay_enum(other)]
+        Other(u8),
+    }
+
+    assert_eq!(Speed::try_from(2_u8), Ok(Speed::Full));
+    assert_eq!(Speed::try_from(0x300_u16), Ok(Speed::High));
+    assert_eq!(Speed::try_from(0x300_u128), Ok(Speed::High));
+    assert_eq!(Speed::try_from(4_u32), Err(4));
+    assert_eq!(u16::from(Speed::High), 0x300);
+    assert_eq!(u64::from(Speed::Low), 1);
+
+    assert_eq!(Class::try_from(3_u8), Ok(Class::Hid));
+    assert_eq!(Class::try_from(0xf1_u16), Ok(Class::Vendor(0xf1)));
+    assert_eq!(Class::try_from(0xff_u8), Ok(Class::Other(0xff)));
+    assert_eq!(u8::from(Class::Vendor(0xf2)), 0xf2);
+    assert_eq!(u32::from(Class::Audio), 1);
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Device {
+        #[overlay(bytes = 0..=1)]
+        speed: Speed,
+
+        #[overlay(byte = 2, unknown = Other)]
+        class: Class,
+
+        #[overlay(bytes = 3..=6, stride = 2)]
+        speeds: [Speed; 2],
+    }
+
+    let mut bytes = [0x03, 0x00, 0xf3, 0x00, 0x02, 0x00, 0x05];
+    let device = Device::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(device.speed(), Ok(Speed::High));
+    assert_eq!(device.class(), Class::Vendor(0xf3));
+    assert_eq!(device.speeds(0), Ok(Speed::Full));
+    assert_eq!(device.speeds(1), Err(5));
+
+    device.set_speed(Speed::Low);
+    device.set_class(Class::Hid);
+    device.set_speeds(1, Speed::High);
+    assert_eq!(
+        device.as_bytes(),
+        &[0x00, 0x01, 0x03, 0x00, 0x02, 0x03, 0x00]
+    );
+
+    // variants carrying data are stored through their conversion, without `unknown`
+    #[overlay_enum]
+    #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+    enum Usage {
+        Idle,
+        #[overlay_enum(range = 1..=14)]
+        Level(u8),
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Meter {
+        #[overlay(byte = 0, bits = 0..4)]
+        usage: Usage,
+
+        #[overlay(byte = 1, element_bits = 4)]
+        history: [Usage; 2],
+
+        #[overlay(bytes = 2..=3)]
+        peaks: [Usage; 2],
+    }
+
+    let mut meter = Meter::new();
+    meter.set_usage(Usage::Level(7));
+    meter.set_history(0, Usage::Level(3));
+    meter.set_history(1, Usage::Idle);
+    meter.set_peaks(1, Usage::Level(14));
+    assert_eq!(meter.as_bytes(), &[0x07, 0x03, 0x00, 0x0e]);
+    assert_eq!(meter.usage(), Ok(Usage::Level(7)));
+    assert_eq!(meter.history(0), Ok(Usage::Level(3)));
+    assert_eq!(meter.peaks(1), Ok(Usage::Level(14)));
+}
+
+#[test]
+fn magic_fields() {
+    #[overlay]
+    #[derive(Debug)]
+    struct BootSector {
+        #[overlay(bytes = 0..=1, le, magic = 0xaa55)]
+        signature: u16,
+
+        #[overlay(byte = 2, bits = 4..8, magic = 4)]
+        version: u8,
+
+        #[overlay(byte = 2, bits = 0..4)]
+        ihl: u8,
+    }
+
+    let mut boot = BootSector::new();
+    assert_eq!(boot.as_bytes(), &[0x55, 0xaa, 0x40]);
+    assert_eq!(boot.signature(), 0xaa55);
+    assert_eq!(boot.version(), 4);
+    assert_eq!(boot.validate(), Ok(()));
+
+    boot.set_ihl(5);
+    assert!(BootSector::overlay_checked(boot.as_bytes()).is_ok());
+
+    let bytes = [0x55, 0xaa, 0x65];
+    assert!(BootSector::overlay(&bytes).is_ok());
+    assert_eq!(
+        BootSector::overlay_checked(&bytes).unwrap_err(),
+        overlay::Error::BadMagic { field: "version" }
+    );
+
+    let mut bytes = [0, 0, 0x45];
+    assert_eq!(
+        BootSector::overlay_checked_mut(&mut bytes).unwrap_err(),
+        overlay::Error::BadMagic { field: "signature" }
+    );
+}
+
+#[test]
+fn reserved_fields() {
+    #[overlay(reject_reserved)]
+    #[derive(Debug)]
+    struct Cdb {
+        #[overlay(byte = 0)]
+        opcode: u8,
+
+        #[overlay(byte = 1, bits = 5..8)]
+        lun: u8,
+
+        #[overlay(byte = 1, bits = 0..5, reserved)]
+        _reserved1: u8,
+
+        #[overlay(bytes = 2..=3, reserved)]
+        _reserved2: u16,
+
+        #[overlay(bytes = 4..=5, le, bits = 4..12, reserved)]
+        _reserved3: u16,
+    }
+
+    let mut bytes = [0x12, 0x20, 0, 0, 0x0f, 0xf0];
+    let cdb = Cdb::overlay_mut(&mut bytes).unwrap();
+    assert_eq!(cdb.check_reserved(), Ok(()));
+    assert_eq!(format!("{cdb:?}"), "Cdb { opcode: 18, lun: 1 }");
+
+    cdb.as_bytes_mut()[5] = 0xf1;
+    assert_eq!(
+        cdb.check_reserved(),
+        Err(overlay::Error::ReservedNotZero {
+            field: "_reserved3"
+        })
+    );
+    cdb.as_bytes_mut()[1] = 0x21;
+    cdb.as_bytes_mut()[3] = 0x80;
+    assert_eq!(
+        Cdb::overlay_checked(cdb.as_bytes()).unwrap_err(),
+        overlay::Error::ReservedNotZero {
+            field: "_reserved1"
+        }
+    );
+
+    cdb.clear_reserved();
+    assert_eq!(cdb.as_bytes(), &[0x12, 0x20, 0, 0, 0x0f, 0xf0]);
+    assert!(Cdb::overlay_checked(&bytes).is_ok());
+}
+
+#[test]
+fn nested_validation() {
+    #[overlay(reject_reserved)]
+    #[derive(Debug)]
+    struct Header {
+        #[overlay(byte = 0, magic = 0x7f)]
+        magic: u8,
+
+        #[overlay(byte = 1, bits = 4..8, reserved)]
+        _reserved: u8,
+
+        #[overlay(byte = 1, bits = 0..4)]
+        version: u8,
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Packet {
+        #[overlay(bytes = 0..=1, nested)]
+        header: Header,
+
+        #[overlay(bytes = 2..=7, stride = 3, nested)]
+        records: [Header; 2],
+    }
+
+    // nested magic fields start out holding their value, in each element too
+    let mut packet = Packet::new();
+    assert_eq!(packet.as_bytes(), &[0x7f, 0, 0x7f, 0, 0, 0x7f, 0, 0]);
+    assert_eq!(packet.validate(), Ok(()));
+
+    packet.as_bytes_mut()[0] = 0;
+    assert_eq!(
+        packet.validate(),
+        Err(overlay::Error::BadMagic { field: "magic" })
+    );
+
+    let mut bytes = [0x7f, 0, 0x7f, 0, 0, 0x7f, 0x10, 0];
+    assert_eq!(
+        Packet::overlay_checked_mut(&mut bytes).unwrap_err(),
+        overlay::Error::ReservedNotZero { field: "_reserved" }
+    );
+    bytes[6] = 0x01;
+    assert!(Packet::overlay_checked(&bytes).is_ok());
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Envelope<P: Overlay> {
+        #[overlay(byte = 0)]
+        kind: u8,
+
+        #[overlay(generic)]
+        payload: P,
+    }
+
+    let envelope = Envelope::<Header>::new();
+    assert_eq!(envelope.as_bytes(), &[0, 0x7f, 0]);
+    assert_eq!(envelope.validate(), Ok(()));
+    assert_eq!(
+        Envelope::<Header>::overlay_checked(&[0, 0x7f, 0x20]).unwrap_err(),
+        overlay::Error::ReservedNotZero { field: "_reserved" }
+    );
+    assert!(Envelope::<[u8; 2]>::overlay_checked(&[0, 0x7f, 0x20]).is_ok());
+}
+
+#[test]
+fn checksum_algorithms() {
+    use overlay::checksum;
+
+    let check = b"123456789";
+    assert_eq!(checksum::crc8(check), 0xf4);
+    assert_eq!(checksum::crc16(check), 0x29b1);
+    assert_eq!(checksum::crc32(check), 0xcbf4_3926);
+    assert_eq!(checksum::sum8(&[1, 2, 3]), 0xfa);
+    assert_eq!(checksum::internet(&[0x00, 0x01, 0xf2]), !0xf201);
+}
+
+#[test]
+fn checksum_fields() {
+    use overlay::checksum::internet;
+
+    fn xor(bytes: &[u8]) -> u8 {
+        bytes.iter().fold(0, |acc, b| acc ^ b)
+    }
+
+    #[overlay]
+    struct Ipv4 {
+        #[overlay(byte = 0)]
+        version_ihl: u8,
+
+        #[overlay(bytes = 10..=11, checksum = internet(0..20))]
+        checksum: u16,
+
+        #[overlay(bytes = 12..=15)]
+        src: u32,
+    }
+
+    #[overlay(endian = little)]
+    struct Frame {
+        #[overlay(bytes = 0..=3)]
+        data: u32,
+
+        #[overlay(bytes = 4..=7, checksum = overlay::checksum::crc32(0..4))]
+        crc: u32,
+
+        #[overlay(byte = 8, bits = 0..4, checksum = xor(0..=8))]
+        parity: u8,
+
+        #[overlay(byte = 8, bits = 4..8)]
+        flags: u8,
+    }
+
+    let mut bytes = [
+        0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xb8, 0x61, 0xc0, 0xa8, 0x00,
+        0x01, 0xc0, 0xa8, 0x00, 0xc7,
+    ];
+    let ip = Ipv4::overlay_mut(&mut bytes).unwrap();
+    assert!(ip.verify_checksum());
+
+    ip.set_src(0xc0a8_0002);
+    assert!(!ip.verify_checksum());
+    ip.update_checksum();
+    assert_eq!(ip.checksum(), 0xb860);
+    assert!(ip.verify_checksum());
+    assert_eq!(ip.version_ihl(), 0x45);
+
+    let mut frame = Frame::new();
+    frame.set_data(u32::from_le_bytes(*b"1234"));
+    frame.update_crc();
+    assert_eq!(frame.crc(), overlay::checksum::crc32(b"1234"));
+    assert!(frame.verify_crc());
+
+    frame.set_flags(0xa);
+    frame.update_parity();
+    assert!(frame.verify_parity());
+    assert_eq!(frame.flags(), 0xa);
+    assert_eq!(frame.parity(), (xor(&frame.as_bytes()[..8]) ^ 0xa0) & 0xf);
+
+    // a function of the same name as one of `overlay::checksum` is used as any other
+    fn crc16(bytes: &[u8]) -> u16 {
+        bytes.iter().map(|&b| u16::from(b)).sum()
+    }
+
+    #[overlay]
+    struct Record {
+        #[overlay(bytes = 0..=2)]
+        data: [u8; 3],
+
+        #[overlay(bytes = 3..=4, checksum = crc16(0..3))]
+        check: u16,
+    }
+
+    let mut record = Record::new();
+    record.set_data(&[1, 2, 3]);
+    record.update_check();
+    assert_eq!(record.check(), 6);
+    assert!(record.verify_check());
+}
+
+#[test]
+fn explicit_size() {
+    #[overlay(size = 36)]
+    #[derive(Debug)]
+    struct Inquiry {
+        #[overlay(byte = 0, bits = 0..5)]
+        device_type: u8,
+
+        #[overlay(bytes = 8..=15)]
+        vendor: [u8; 8],
+    }
+
+    assert_eq!(Inquiry::BYTE_LEN, 36);
+    assert_eq!(Inquiry::new().as_bytes().len(), 36);
+    assert_eq!(
+        Inquiry::overlay(&[0; 35]).unwrap_err(),
+        overlay::Error::InsufficientLength
+    );
+    assert!(Inquiry::overlay(&[0; 36]).is_ok());
+}
+
+#[test]
+fn auto_layout() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Ipv4 {
+        #[overlay(bits = 4)]
+        version: u8,
+        #[overlay(bits = 4)]
+        ihl: u8,
+        #[overlay(bits = 6)]
+        dscp: u8,
+        #[overlay(bits = 2)]
+        ecn: u8,
+        #[overlay(bytes = 2)]
+        total_length: u16,
+        #[overlay(bytes = 2)]
+        identification: u16,
+        #[overlay(bits = 3)]
+        flags: u8,
+        #[overlay(bits = 13)]
+        fragment_offset: u16,
+        #[overlay(bytes = 1)]
+        ttl: u8,
+        #[overlay(bytes = 1)]
+        protocol: u8,
+        #[overlay(bytes = 2)]
+        checksum: u16,
+        #[overlay(bytes = 4)]
+        src: [u8; 4],
+        #[overlay(bytes = 4)]
+        dst: [u8; 4],
+    }
+
+    let mut bytes = [
+        0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xb8, 0x61, 0xc0, 0xa8, 0x00,
+        0x01, 0xc0, 0xa8, 0x00, 0xc7,
+    ];
+    let ip = Ipv4::overlay_mut(&mut bytes).unwrap();
+
+    assert_eq!(Ipv4::BYTE_LEN, 20);
+    assert_eq!(ip.version(), 4);
+    assert_eq!(ip.ihl(), 5);
+    assert_eq!(ip.total_length(), 0x73);
+    assert_eq!(ip.flags(), 0b010);
+    assert_eq!(ip.fragment_offset(), 0);
+    assert_eq!(ip.ttl(), 0x40);
+    assert_eq!(ip.protocol(), 0x11);
+    assert_eq!(ip.checksum(), 0xb861);
+    assert_eq!(ip.dst(), &[0xc0, 0xa8, 0x00, 0xc7]);
+
+    ip.set_fragment_offset(0x1234);
+    ip.set_ecn(0b11);
+    assert_eq!(
+        &ip.as_bytes()[..8],
+        &[0x45, 0x03, 0x00, 0x73, 0x00, 0x00, 0x52, 0x34]
+    );
+
+    // little-endian fields fill each byte from the bottom, and follow explicit fields
+    #[overlay(endian = little)]
+    struct Flags {
+        #[overlay(byte = 0)]
+        kind: u8,
+        #[overlay(bits = 3)]
+        a: u8,
+        #[overlay(bits = 12)]
+        b: u16,
+        #[overlay(bits = 1)]
+        c: bool,
+        #[overlay(bytes = 2)]
+        d: u16,
+    }
+
+    let mut flags = Flags::new();
+    flags.set_a(0b101);
+    flags.set_b(0xabc);
+    flags.set_c(true);
+    flags.set_d(0x1234);
+    assert_eq!(flags.as_bytes(), &[0x00, 0xe5, 0xd5, 0x34, 0x12]);
+    assert_eq!(flags.kind(), 0);
+}
+
+#[test]
+fn msb0_bit_order() {
+    #[derive(Debug, PartialEq, Eq)]
+    enum Ecn {
+        NotEct = 0,
+        Ect1 = 1,
+        Ect0 = 2,
+        Ce = 3,
+    }
+
+    impl TryFrom<u8> for Ecn {
+        type Error = ();
+
+        fn try_from(v: u8) -> Result<Self, Self::Error> {
+            Ok(match v {
+                0 => Self::NotEct,
+                1 => Self::Ect1,
+                2 => Self::Ect0,
+                3 => Self::Ce,
+                _ => return Err(()),
+            })
+        }
+    }
+
+    #[overlay]
+    struct Lsb0 {
+        #[overlay(byte = 0, bits = 4..8)]
+        version: u8,
+        #[overlay(byte = 1, bits = 0..2)]
+        ecn: Ecn,
+        #[overlay(bytes = 6..=7, bit = 14)]
+        dont_fragment: bool,
+        #[overlay(bytes = 6..=7, bits = 0..13)]
+        fragment_offset: u16,
+    }
+
+    #[overlay(bit_order = msb0)]
+    struct Msb0 {
+        #[overlay(byte = 0, bits = 0..4)]
+        version: u8,
+        #[overlay(byte = 1, bits = 6..8)]
+        ecn: Ecn,
+        #[overlay(bytes = 6..=7, bit = 1)]
+        dont_fragment: bool,
+        #[overlay(bytes = 6..=7, bits = 3..16)]
+        fragment_offset: u16,
+    }
+
+    #[overlay]
+    struct Mixed {
+        #[overlay(byte = 0, bits = 0..4, bit_order = msb0)]
+        version: u8,
+        #[overlay(byte = 0, bits = 0..4)]
+        ihl: u8,
+    }
+
+    let mut lsb0 = Lsb0::new();
+    lsb0.set_version(4);
+    lsb0.set_ecn(Ecn::Ect0);
+    lsb0.set_dont_fragment(true);
+    lsb0.set_fragment_offset(0x123);
+
+    let mut msb0 = Msb0::new();
+    msb0.set_version(4);
+    msb0.set_ecn(Ecn::Ect0);
+    msb0.set_dont_fragment(true);
+    msb0.set_fragment_offset(0x123);
+
+    assert_eq!(lsb0.as_bytes(), msb0.as_bytes());
+    assert_eq!(msb0.as_bytes(), &[0x40, 0x02, 0, 0, 0, 0, 0x41, 0x23]);
+    assert_eq!(msb0.version(), 4);
+    assert_eq!(msb0.ecn(), Ok(Ecn::Ect0));
+    assert!(msb0.dont_fragment());
+    assert_eq!(msb0.fragment_offset(), 0x123);
+
+    let mut mixed = Mixed::new();
+    mixed.set_version(4);
+    mixed.set_ihl(5);
+    assert_eq!(mixed.as_bytes(), &[0x45]);
+
+    // bit-packed elements are indexed from the start of the range, as the diagram reads
+    #[overlay(bit_order = msb0)]
+    #[derive(Debug)]
+    struct Packed {
+        #[overlay(byte = 0, bits = 0..=3, element_bits = 2)]
+        modes: [u8; 2],
+        #[overlay(byte = 1)]
+        flags: [bool; 8],
+        #[overlay(bytes = 2..=3, element_bits = 4)]
+        nibbles: [u8; 3],
+    }
+
+    let mut bytes = [0b1110_0000, 0b1000_0001, 0, 0];
+    let packed = Packed::overlay_mut(&mut bytes).unwrap();
+    assert_eq!(packed.modes(0), 3);
+    assert_eq!(packed.modes(1), 2);
+    assert!(packed.flags(0));
+    assert!(!packed.flags(1));
+    assert!(packed.flags(7));
+
+    packed.set_modes(1, 1);
+    packed.set_flags(1, true);
+    packed.set_nibbles(0, 0xa);
+    packed.set_nibbles(2, 0xc);
+    assert_eq!(packed.as_bytes(), &[0b1101_0000, 0b1100_0001, 0xa0, 0xc0]);
+}
+
+#[test]
+fn absolute_bit_offsets() {
+    #[overlay]
+    struct Intel {
+        #[overlay(bit_offset = 37, bit_len = 11)]
+        a: u16,
+        #[overlay(bit_offset = 3, bit_len = 1)]
+        b: bool,
+        #[overlay(bit_offset = 5, bit_len = 20)]
+        c: u32,
+        #[overlay(bit_offset = 60, bit_len = 64)]
+        d: u64,
+        #[overlay(bit_len = 4, bit_offset = 124)]
+        e: u8,
+    }
+
+    #[overlay(bit_order = msb0)]
+    struct Motorola {
+        #[overlay(bit_offset = 37, bit_len = 11)]
+        a: u16,
+        #[overlay(bit_offset = 3, bit_len = 1)]
+        b: bool,
+        #[overlay(bit_offset = 5, bit_len = 20)]
+        c: u32,
+        #[overlay(bit_offset = 60, bit_len = 64)]
+        d: u64,
+        // continues from the previous field
+        #[overlay(bits = 4)]
+        e: u8,
+    }
+
+    let mut bytes: [u8; 16] = core::array::from_fn(|i| (i as u8).wrapping_mul(0x9d) ^ 0x5a);
+    let get = |v: u128, offset: u32, len: u32| (v >> offset) & (!0 >> (128 - len));
+
+    let le = u128::from_le_bytes(bytes);
+    let intel = Intel::overlay(&bytes).unwrap();
+    assert_eq!(intel.a() as u128, get(le, 37, 11));
+    assert_eq!(intel.b(), get(le, 3, 1) != 0);
+    assert_eq!(intel.c() as u128, get(le, 5, 20));
+    assert_eq!(intel.d() as u128, get(le, 60, 64));
+    assert_eq!(intel.e() as u128, get(le, 124, 4));
+
+    // msb0 bit `n` is bit `127 - n` of the big-endian value
+    let be = u128::from_be_bytes(bytes);
+    let motorola = Motorola::overlay(&bytes).unwrap();
+    assert_eq!(motorola.a() as u128, get(be, 128 - 37 - 11, 11));
+    assert_eq!(motorola.b(), get(be, 128 - 3 - 1, 1) != 0);
+    assert_eq!(motorola.c() as u128, get(be, 128 - 5 - 20, 20));
+    assert_eq!(motorola.d() as u128, get(be, 128 - 60 - 64, 64));
+    assert_eq!(motorola.e() as u128, get(be, 0, 4));
+
+    let intel = Intel::overlay_mut(&mut bytes).unwrap();
+    intel.set_a(0x5a5);
+    intel.set_c(0xfedcb);
+    intel.set_d(0x0123_4567_89ab_cdef);
+    let le = u128::from_le_bytes(bytes);
+    assert_eq!(get(le, 37, 11), 0x5a5);
+    assert_eq!(get(le, 5, 20), 0xfedcb);
+    assert_eq!(get(le, 60, 64), 0x0123_4567_89ab_cdef);
+
+    let motorola = Motorola::overlay_mut(&mut bytes).unwrap();
+    motorola.set_a(0x5a5);
+    motorola.set_d(0x0123_4567_89ab_cdef);
+    let be = u128::from_be_bytes(bytes);
+    assert_eq!(get(be, 128 - 37 - 11, 11), 0x5a5);
+    assert_eq!(get(be, 128 - 60 - 64, 64), 0x0123_4567_89ab_cdef);
+
+    // an unaligned 128-bit field spans 17 bytes, with its top bits in the last
+    #[overlay]
+    struct Widest {
+        #[overlay(bit_offset = 0, bit_len = 3)]
+        before: u8,
+        #[overlay(bit_offset = 3, bit_len = 128)]
+        unaligned: u128,
+        #[overlay(bit_offset = 131, bit_len = 5)]
+        after: u8,
+        #[overlay(bit_offset = 136, bit_len = 128)]
+        aligned: u128,
+    }
+
+    let value = 0x8123_4567_89ab_cdef_fedc_ba98_7654_3211_u128;
+    let mut widest = Widest::new();
+    widest.set_before(0x7);
+    widest.set_after(0x1f);
+    widest.set_aligned(!0);
+    widest.set_unaligned(value);
+    assert_eq!(widest.unaligned(), value);
+    assert_eq!((widest.before(), widest.after()), (0x7, 0x1f));
+    let low = u128::from_le_bytes(widest.as_bytes()[..16].try_into().unwrap());
+    assert_eq!(low, value << 3 | 0x7);
+    assert_eq!(widest.as_bytes()[16], (0x1f << 3) | (value >> 125) as u8);
+    assert!(widest.as_bytes()[17..].iter().all(|&b| b == 0xff));
+
+    widest.set_unaligned(0);
+    assert_eq!(
+        widest.as_bytes()[..17],
+        [0x7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xf8]
+    );
+
+    // and big-endian with msb0, where the top bits are in the first byte
+    #[overlay(bit_order = msb0)]
+    struct WidestMotorola {
+        #[overlay(bit_offset = 0, bit_len = 5)]
+        before: u8,
+        #[overlay(bit_offset = 5, bit_len = 128)]
+        unaligned: i128,
+        #[overlay(bit_offset = 133, bit_len = 3)]
+        after: u8,
+    }
+
+    let value = -0x0123_4567_89ab_cdef_fedc_ba98_7654_3211_i128;
+    let mut bytes = [0xff; 17];
+    let motorola = WidestMotorola::overlay_mut(&mut bytes).unwrap();
+    assert_eq!(motorola.unaligned(), -1);
+    motorola.set_unaligned(value);
+    assert_eq!(motorola.unaligned(), value);
+    assert_eq!((motorola.before(), motorola.after()), (0x1f, 0x7));
+    let low = u128::from_be_bytes(bytes[1..].try_into().unwrap());
+    assert_eq!(low, (value as u128) << 3 | 0x7);
+    assert_eq!(bytes[0], (0x1f << 3) | (value as u128 >> 125) as u8);
+
+    // `new()` writes the top bits of a magic value to the 17th byte
+    #[overlay]
+    #[derive(Debug)]
+    struct WidestMagic {
+        #[overlay(
+            bit_offset = 4,
+            bit_len = 128,
+            magic = 0xf000_0000_0000_0000_0000_0000_0000_000f
+        )]
+        magic: u128,
+    }
+
+    let magic = WidestMagic::new();
+    assert_eq!(magic.as_bytes()[..2], [0xf0, 0]);
+    assert_eq!(magic.as_bytes()[16], 0x0f);
+    assert_eq!(magic.validate(), Ok(()));
+    let mut bytes = [0; 17];
+    bytes.copy_from_slice(magic.as_bytes());
+    bytes[16] = 0x07;
+    assert_eq!(
+        WidestMagic::overlay_checked(&bytes).unwrap_err(),
+        overlay::Error::BadMagic { field: "magic" }
+    );
+}
+
+#[test]
+fn generic_structs() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Frame<const N: usize> {
+        #[overlay(bytes = 0..=1)]
+        len: u16,
+
+        #[overlay(byte = 2)]
+        flags: u8,
+
+        #[overlay(generic)]
+        payload: [u8; N],
+    }
+
+    assert_eq!(Frame::<4>::BYTE_LEN, 7);
+    assert_eq!(Frame::<0>::BYTE_LEN, 3);
+
+    let mut frame = Frame::<4>::new();
+    frame.set_len(4);
+    frame.payload_mut().copy_from_slice(&[1, 2, 3, 4]);
+    assert_eq!(frame.as_bytes(), &[0, 4, 0, 1, 2, 3, 4]);
+    assert_eq!(
+        format!("{frame:?}"),
+        "Frame { len: 4, flags: 0, payload: [1, 2, 3, 4] }"
+    );
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Inner {
+        #[overlay(bytes = 0..=1)]
+        value: u16,
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Envelope<P: Overlay> {
+        #[overlay(byte = 0)]
+        kind: u8,
+
+        #[overlay(generic)]
+        payload: P,
+    }
+
+    assert_eq!(Envelope::<Inner>::BYTE_LEN, 3);
+    assert_eq!(Envelope::<Frame<2>>::BYTE_LEN, 6);
+
+    let mut bytes = [7, 0x12, 0x34, 0xff];
+    let envelope = Envelope::<Inner>::overlay_mut(&mut bytes).unwrap();
+    assert_eq!(envelope.kind(), 7);
+    assert_eq!(envelope.payload().value(), 0x1234);
+
+    envelope.payload_mut().set_value(0xabcd);
+    envelope.set_kind(8);
+    assert_eq!(envelope.as_bytes(), &[8, 0xab, 0xcd]);
+    assert_eq!(
+        format!("{envelope:?}"),
+        "Envelope { kind: 8, payload: Inner { value: 43981 } }"
+    );
+
+    assert!(Envelope::<Inner>::overlay(&[0; 2]).is_err());
+}
+
+#[test]
+fn accessor_names() {
+    #[overlay]
+    #[derive(Debug)]
+    struct Point {
+        #[overlay(byte = 0, getter = x_coord, setter = move_x)]
+        x: u8,
+
+        #[overlay(byte = 1)]
+        y: u8,
+    }
+
+    #[overlay(getter_prefix = get_)]
+    #[derive(Debug)]
+    struct Shape {
+        #[overlay(bytes = 0..=1, nested, setter = origin)]
+        new: Point,
+
+        #[overlay(byte = 2, getter = count)]
+        len: u8,
+
+        #[overlay(len = len)]
+        points: [Point],
+    }
+
+    let mut bytes = [1, 2, 1, 3, 4, 0xff];
+    let shape = Shape::overlay_mut(&mut bytes).unwrap();
+    assert_eq!(shape.get_new().x_coord(), 1);
+    assert_eq!(shape.count(), 1);
+    assert_eq!(shape.get_points().unwrap()[0].y(), 4);
+
+    shape.origin().move_x(5);
+    shape.set_len(0);
+    assert!(shape.get_points().unwrap().is_empty());
+    assert_eq!(
+        format!("{shape:?}"),
+        "Shape { new: Point { x: 5, y: 2 }, len: 0, points: Ok([]) }"
+    );
+}
+
+#[test]
+fn explicit_kinds() {
+    type Lba = u32;
+    type Offset = i16;
+    type Flag = bool;
+    type Mac = [u8; 6];
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Address {
+        #[overlay(bytes = 0..=5, kind = bytes)]
+        mac: Mac,
+    }
+
+    #[overlay]
+    #[derive(Debug)]
+    struct Entry {
+        #[overlay(bytes = 0..=3, kind = int)]
+        lba: Lba,
+
+        #[overlay(bytes = 4..=5, bits = 0..12, kind = int)]
+        offset: Offset,
+
+        #[overlay(byte = 4, bit = 7, kind = bool)]
+        valid: Flag,
+
+        #[overlay(bytes = 6..=7, element_bits = 4, kind = int)]
+        deltas: [Offset; 4],
+
+        #[overlay(bytes = 8..=13, kind = nested)]
+        address: Address,
+    }
+
+    let mut bytes = [0; Entry::BYTE_LEN];
+    let entry = Entry::overlay_mut(&mut bytes).unwrap();
+    entry.set_lba(0x1234_5678);
+    entry.set_offset(-2);
+    entry.set_valid(true);
+    entry.set_deltas(1, -1);
+    entry.address_mut().set_mac(&[1, 2, 3, 4, 5, 6]);
+
+    assert_eq!(entry.lba(), 0x1234_5678);
+    assert_eq!(entry.offset(), -2);
+    assert!(entry.valid());
+    assert_eq!(entry.deltas(0), 0);
+    assert_eq!(entry.deltas(1), -1);
+    assert_eq!(entry.address().mac(), &[1, 2, 3, 4, 5, 6]);
+    assert_eq!(
+        bytes,
+        [0x12, 0x34, 0x56, 0x78, 0x8f, 0xfe, 0x00, 0xf0, 1, 2, 3, 4, 5, 6]
+    );
+}
+
+#[test]
+fn compile_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
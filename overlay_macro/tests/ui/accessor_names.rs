@@ -0,0 +1,67 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct OwnItems {
+    #[overlay(byte = 0)]
+    new: u8,
+    #[overlay(byte = 1, getter = as_bytes)]
+    bytes: u8,
+    #[overlay(byte = 2, setter = BYTE_LEN)]
+    len: u8,
+}
+
+#[overlay]
+pub struct TraitMethods {
+    #[overlay(byte = 0)]
+    overlay: u8,
+    #[overlay(byte = 1, setter = overlay_mut)]
+    a: u8,
+    #[overlay(byte = 2)]
+    validate: u8,
+    #[overlay(byte = 3, getter = overlay_checked)]
+    b: u8,
+    #[overlay(bytes = 4..=5, nested, setter = overlay_checked_mut)]
+    c: OwnItems,
+}
+
+#[overlay]
+pub struct Fields {
+    #[overlay(byte = 0)]
+    x: u8,
+    #[overlay(byte = 1)]
+    set_x: u8,
+    #[overlay(bytes = 2..=3)]
+    list: [u16; 1],
+    #[overlay(byte = 4)]
+    list_len: u8,
+    #[overlay(byte = 5, getter = x)]
+    y: u8,
+}
+
+#[overlay(getter_prefix = set_)]
+pub struct Prefix {
+    #[overlay(byte = 0)]
+    x: u8,
+}
+
+#[overlay(reject_reserved)]
+pub struct Reserved {
+    #[overlay(byte = 0, reserved)]
+    spare: u8,
+    #[overlay(byte = 1)]
+    check_reserved: u8,
+}
+
+#[overlay]
+pub struct RenameReserved {
+    #[overlay(byte = 0, reserved, getter = spare)]
+    a: u8,
+}
+
+#[overlay]
+pub struct RenameMagic {
+    #[overlay(byte = 0, magic = 1, setter = set_signature)]
+    a: u8,
+}
+
+fn main() {}
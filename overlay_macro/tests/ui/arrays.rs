@@ -0,0 +1,97 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct Indivisible {
+    #[overlay(bytes = 0..=4)]
+    a: [u16; 2],
+}
+
+#[overlay]
+pub struct ZeroStride {
+    #[overlay(bytes = 0..=3, stride = 0)]
+    a: [u16; 2],
+}
+
+#[overlay]
+pub struct StrideOverflow {
+    #[overlay(bytes = 0..=3, stride = 3)]
+    a: [u16; 2],
+}
+
+#[overlay]
+pub struct FloatStride {
+    #[overlay(bytes = 0..=6, stride = 3)]
+    a: [f32; 2],
+}
+
+#[overlay]
+pub struct WideElements {
+    #[overlay(bytes = 0..=35)]
+    a: [usize; 2],
+}
+
+#[overlay]
+pub struct ArrayBits {
+    #[overlay(bytes = 0..=3, bits = 0..=15)]
+    a: [u16; 2],
+}
+
+#[overlay]
+pub struct PackedStride {
+    #[overlay(byte = 0, stride = 1)]
+    a: [bool; 8],
+}
+
+#[overlay]
+pub struct ElementBitsRange {
+    #[overlay(bytes = 0..=1, element_bits = 0)]
+    a: [u8; 4],
+}
+
+#[overlay]
+pub struct PackedOverflow {
+    #[overlay(byte = 0)]
+    a: [bool; 9],
+}
+
+#[overlay]
+pub struct PackedBitRange {
+    #[overlay(byte = 0, bits = 0..=6)]
+    a: [bool; 4],
+}
+
+#[overlay]
+pub struct PackedFloat {
+    #[overlay(bytes = 0..=7, element_bits = 32)]
+    a: [f32; 2],
+}
+
+#[overlay]
+pub struct InvalidElement {
+    #[overlay(bytes = 0..=3)]
+    a: [bool; 2],
+    #[overlay(bytes = 4..=5)]
+    b: [[u8; 1]; 2],
+}
+
+type Raw = [u8; 4];
+
+#[overlay]
+pub struct StrideOnAlias {
+    #[overlay(bytes = 0..=3, kind = bytes, stride = 2)]
+    a: Raw,
+}
+
+#[overlay]
+pub struct PackedEmpty {
+    #[overlay(byte = 0)]
+    a: [bool; 0],
+}
+
+#[overlay]
+pub struct PackedTooWide {
+    #[overlay(bytes = 0..=16, element_bits = 8)]
+    a: [u8; 17],
+}
+
+fn main() {}
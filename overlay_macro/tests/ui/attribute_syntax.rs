@@ -0,0 +1,63 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct InvalidSpecifier {
+    #[overlay(byte = 0, colour = 1)]
+    a: u8,
+}
+
+#[overlay]
+pub struct DuplicateSpecifier {
+    #[overlay(byte = 0, byte = 1)]
+    a: u8,
+}
+
+#[overlay]
+pub struct SingularRange {
+    #[overlay(byte = 0..=1)]
+    a: u16,
+}
+
+#[overlay]
+pub struct BackwardsRange {
+    #[overlay(bytes = 3..=1)]
+    a: u16,
+}
+
+#[overlay]
+pub struct EmptyRange {
+    #[overlay(bytes = 1..1)]
+    a: u16,
+}
+
+#[overlay]
+pub struct MissingComma {
+    #[overlay(byte = 0 bits = 1)]
+    a: u8,
+}
+
+#[overlay]
+pub struct InvalidBitOrder {
+    #[overlay(byte = 0, bits = 0..=3, bit_order = lsb1)]
+    a: u8,
+}
+
+#[overlay(endian = middle)]
+pub struct InvalidEndian {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay(packed)]
+pub struct InvalidStructSpecifier {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay]
+pub struct BadChecksum {
+    #[overlay(byte = 0, checksum = crc8(4))]
+    a: u8,
+}
+
+fn main() {}
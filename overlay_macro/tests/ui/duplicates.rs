@@ -0,0 +1,100 @@
+use overlay_macro::{overlay, overlay_enum};
+
+#[overlay_enum]
+pub enum Mode {
+    A,
+    #[overlay_enum(other)]
+    Other(u8),
+}
+
+#[overlay]
+pub struct Body {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay]
+pub struct Fields {
+    #[overlay(byte = 0, unknown = Other, unknown = Other)]
+    unknown: Mode,
+    #[overlay(byte = 1, magic = 1, magic = 2)]
+    magic: u8,
+    #[overlay(bit_offset = 16, bit_offset = 17, bit_len = 1)]
+    bit_offset: u8,
+    #[overlay(bit_offset = 16, bit_len = 1, bit_len = 2)]
+    bit_len: u8,
+    #[overlay(byte = 3, bits = 0..=3, bit_order = msb0, bit_order = lsb0)]
+    bit_order: u8,
+    #[overlay(byte = 4, checksum = crc8(0..4), checksum = crc8(0..4))]
+    checksum: u8,
+    #[overlay(byte = 5, getter = a, getter = b)]
+    getter: u8,
+    #[overlay(byte = 6, setter = a, setter = b)]
+    setter: u8,
+    #[overlay(byte = 7, kind = int, kind = int)]
+    kind: u8,
+    #[overlay(byte = 8, with = Into::into, with = Into::into)]
+    with: u8,
+    #[overlay(len = a, len = b)]
+    len: [u8],
+}
+
+#[overlay]
+pub struct MoreFields {
+    #[overlay(bytes = 0..=1, le, be)]
+    endian: u16,
+    #[overlay(bytes = 2..=5, stride = 2, stride = 2)]
+    stride: [u8; 2],
+    #[overlay(byte = 6, element_bits = 1, element_bits = 1)]
+    element_bits: [bool; 8],
+    #[overlay(byte = 7, bits = 0..=3, bits = 4..=7)]
+    bits: u8,
+    #[overlay(bits = 4, bits = 4)]
+    width: u8,
+}
+
+#[overlay(tag(byte = 0), tag(byte = 1))]
+pub enum DuplicateTag {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(bit_order = msb0, bit_order = msb0)]
+pub struct DuplicateBitOrder {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay(size = 1, size = 2)]
+pub struct DuplicateSize {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay(getter_prefix = get_, getter_prefix = read_)]
+pub struct DuplicateGetterPrefix {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay(endian = big, endian = little)]
+pub struct DuplicateEndian {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay(tag(byte = 0))]
+pub enum DuplicateVariantTag {
+    #[overlay(tag = 0)]
+    #[overlay(tag = 1)]
+    A(Body),
+}
+
+#[overlay_enum]
+pub enum DuplicateVariantValue {
+    #[overlay_enum(range = 0..=3)]
+    #[overlay_enum(other)]
+    A(u8),
+}
+
+fn main() {}
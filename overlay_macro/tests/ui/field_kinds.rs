@@ -0,0 +1,43 @@
+use overlay_macro::overlay;
+
+type Lba = u32;
+
+#[overlay]
+pub struct UnknownKind {
+    #[overlay(bytes = 0..=3, kind = integer)]
+    lba: Lba,
+}
+
+#[overlay]
+pub struct NestedAndKind {
+    #[overlay(bytes = 0..=3, nested, kind = int)]
+    lba: Lba,
+}
+
+#[overlay]
+pub struct TooNarrow {
+    #[overlay(bytes = 0..=4, kind = int)]
+    lba: Lba,
+}
+
+#[overlay]
+pub struct TailKind {
+    #[overlay(tail, kind = bytes)]
+    rest: [u8],
+}
+
+#[overlay]
+pub struct WithAndKind {
+    #[overlay(bytes = 0..=3, with = Into::into, kind = int)]
+    lba: Lba,
+}
+
+#[overlay]
+pub struct WrappingInt {
+    #[overlay(byte = 0, kind = int)]
+    count: core::num::Wrapping<u8>,
+    #[overlay(bytes = 1..=2, kind = int)]
+    counts: [core::num::Wrapping<u8>; 2],
+}
+
+fn main() {}
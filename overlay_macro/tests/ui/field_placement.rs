@@ -0,0 +1,182 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct MissingAttribute {
+    #[overlay(byte = 0)]
+    a: u8,
+    b: u8,
+}
+
+#[overlay]
+pub struct NoByte {
+    #[overlay(bits = 0..=3)]
+    a: u8,
+}
+
+#[overlay]
+pub struct TailAndLen {
+    #[overlay(byte = 0)]
+    count: u8,
+    #[overlay(tail, len = count)]
+    a: [u8],
+}
+
+#[overlay]
+pub struct TailWithRange {
+    #[overlay(tail, bytes = 0..=1)]
+    a: [u8],
+}
+
+#[overlay]
+pub struct BitOffsetWithoutLen {
+    #[overlay(bit_offset = 3)]
+    a: u8,
+}
+
+#[overlay]
+pub struct ZeroWidth {
+    #[overlay(bits = 0)]
+    a: u8,
+}
+
+#[overlay]
+pub struct WidthAndRange {
+    #[overlay(bits = 4, byte = 0)]
+    a: u8,
+}
+
+#[overlay]
+pub struct BitOffsetEndian {
+    #[overlay(bit_offset = 3, bit_len = 9, be)]
+    a: u16,
+}
+
+#[overlay]
+pub struct UnalignedTooWide {
+    #[overlay(bit_offset = 3, bit_len = 128)]
+    a: u128,
+    #[overlay(bit_offset = 131, bit_len = 129)]
+    b: u128,
+}
+
+#[overlay]
+pub struct BitsOutsideBytes {
+    #[overlay(byte = 0, bits = 4..=8)]
+    a: u8,
+}
+
+#[overlay]
+pub struct BoolRange {
+    #[overlay(byte = 0, bits = 0..=1)]
+    a: bool,
+}
+
+#[overlay]
+pub struct BoolOutsideBytes {
+    #[overlay(byte = 0, bit = 8)]
+    a: bool,
+}
+
+#[overlay]
+pub struct NestedBits {
+    #[overlay(byte = 0, bits = 0..=3, nested)]
+    a: NoByte,
+}
+
+#[overlay]
+pub struct UnknownLen {
+    #[overlay(len = count)]
+    a: [u8],
+}
+
+#[overlay]
+pub struct TwoTails {
+    #[overlay(tail)]
+    a: [u8],
+    #[overlay(tail)]
+    b: [u8],
+}
+
+#[overlay]
+pub struct LenThenTail {
+    #[overlay(byte = 0)]
+    count: u8,
+    #[overlay(len = count)]
+    a: [u8],
+    #[overlay(tail)]
+    b: [u8],
+}
+
+#[overlay]
+pub struct TailThenLen {
+    #[overlay(byte = 0)]
+    count: u8,
+    #[overlay(tail)]
+    a: [u8],
+    #[overlay(len = count)]
+    b: [u8],
+}
+
+#[overlay]
+pub struct BitOffsetWithRange {
+    #[overlay(byte = 0, bit_offset = 3, bit_len = 2)]
+    a: u8,
+}
+
+#[overlay]
+pub struct ReservedBitsOutside {
+    #[overlay(byte = 0, bits = 4..=8, reserved)]
+    _spare: (),
+}
+
+#[overlay]
+pub struct ReservedBitsTooWide {
+    #[overlay(bytes = 0..=16, bits = 0..=3, reserved)]
+    _spare: (),
+}
+
+#[overlay]
+pub struct BitOffsetOverflows {
+    #[overlay(bit_offset = 4294967295, bit_len = 2)]
+    a: u8,
+}
+
+#[overlay]
+pub struct AutoLayoutOverflows {
+    #[overlay(bit_offset = 4294967000, bit_len = 8)]
+    a: u8,
+    #[overlay(bits = 4000)]
+    b: u8,
+}
+
+#[overlay]
+pub struct WidthOverflows {
+    #[overlay(bytes = 536870912)]
+    a: u8,
+}
+
+#[overlay]
+pub struct RangeLenOverflows {
+    #[overlay(byte = 0, bits = 0..=4294967295)]
+    a: u8,
+}
+
+#[overlay]
+pub struct BytesTooFar {
+    #[overlay(bytes = 0..=536870912, bits = 0..2)]
+    a: u8,
+}
+
+#[overlay]
+pub struct PackedLenOverflows {
+    #[overlay(bytes = 0..=1, element_bits = 128)]
+    a: [u8; 4294967296],
+}
+
+#[overlay]
+pub struct StrideOverflows {
+    #[overlay(bytes = 0..=3, stride = 4294967295)]
+    a: [u8; 18446744073709551615],
+}
+
+fn main() {}
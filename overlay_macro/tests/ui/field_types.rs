@@ -0,0 +1,101 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct Tuple {
+    #[overlay(bytes = 0..=1)]
+    a: (u8, u8),
+}
+
+#[overlay]
+pub struct TailNotBytes {
+    #[overlay(tail)]
+    a: [u16],
+}
+
+#[overlay]
+pub struct LenNotSlice {
+    #[overlay(byte = 0)]
+    count: u8,
+    #[overlay(len = count)]
+    a: u8,
+}
+
+#[overlay]
+pub struct FloatSize {
+    #[overlay(bytes = 0..=1)]
+    a: f32,
+}
+
+#[overlay]
+pub struct FloatBits {
+    #[overlay(bytes = 0..=3, bits = 0..=3)]
+    a: f32,
+}
+
+#[overlay]
+pub struct ByteArrayBits {
+    #[overlay(bytes = 0..=3, bits = 0..=3)]
+    a: [u8; 4],
+}
+
+#[overlay]
+pub struct TooWide {
+    #[overlay(bytes = 0..=16)]
+    a: u128,
+}
+
+#[overlay]
+pub struct OddEnum {
+    #[overlay(bytes = 0..=2)]
+    a: Colour,
+}
+
+#[overlay]
+pub struct StrideOnInteger {
+    #[overlay(byte = 0, stride = 1)]
+    a: u8,
+}
+
+#[overlay]
+pub struct ElementBitsOnInteger {
+    #[overlay(byte = 0, element_bits = 1)]
+    a: u8,
+}
+
+#[overlay]
+pub struct UnknownOnInteger {
+    #[overlay(byte = 0, unknown = Other)]
+    a: u8,
+}
+
+#[overlay]
+pub struct MagicOnBool {
+    #[overlay(byte = 0, bit = 0, magic = 1)]
+    a: bool,
+}
+
+#[overlay]
+pub struct MagicTooBig {
+    #[overlay(byte = 0, magic = 0x100)]
+    a: u8,
+}
+
+#[overlay]
+pub struct ChecksumOnArray {
+    #[overlay(bytes = 0..=1, checksum = crc8(2..4))]
+    a: [u8; 2],
+}
+
+#[overlay]
+pub struct ArrayLength {
+    #[overlay(bytes = 0..=3)]
+    a: [u16; LEN],
+}
+
+const LEN: usize = 2;
+
+pub enum Colour {
+    Red,
+}
+
+fn main() {}
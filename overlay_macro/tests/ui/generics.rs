@@ -0,0 +1,62 @@
+use overlay::Overlay;
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct Lifetime<'a> {
+    #[overlay(byte = 0)]
+    a: &'a u8,
+}
+
+#[overlay]
+pub struct Unmarked<T> {
+    #[overlay(byte = 0)]
+    a: u8,
+    b: T,
+}
+
+#[overlay]
+pub struct MarkedFixed {
+    #[overlay(generic)]
+    a: u8,
+}
+
+#[overlay]
+pub struct GenericRange<T> {
+    #[overlay(generic, byte = 0)]
+    a: T,
+}
+
+#[overlay]
+pub struct GenericTail<T> {
+    #[overlay(generic)]
+    a: T,
+    #[overlay(tail)]
+    b: [u8],
+}
+
+#[overlay]
+pub struct UnusedConst<const N: usize> {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay]
+pub struct Envelope<P> {
+    #[overlay(byte = 0)]
+    kind: u8,
+    #[overlay(generic)]
+    body: P,
+}
+
+#[overlay(tag(byte = 0))]
+pub enum GenericEnum<T> {
+    #[overlay(tag = 0)]
+    A(MarkedFixed),
+    #[overlay(tag = 1)]
+    B(GenericRange<T>),
+}
+
+fn main() {
+    // not every byte is a valid `bool`
+    let _ = Envelope::<bool>::overlay(&[0, 2]);
+}
@@ -0,0 +1,14 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct Tuple(u8);
+
+#[overlay]
+pub struct Unit;
+
+#[overlay]
+pub union Union {
+    a: u8,
+}
+
+fn main() {}
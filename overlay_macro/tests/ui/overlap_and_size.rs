@@ -0,0 +1,19 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct Overlap {
+    #[overlay(bytes = 0..=1)]
+    a: u16,
+    #[overlay(byte = 1)]
+    b: u8,
+}
+
+#[overlay(size = 2)]
+pub struct TooSmall {
+    #[overlay(byte = 0)]
+    a: u8,
+    #[overlay(byte = 2)]
+    b: u8,
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+use overlay_macro::overlay;
+
+// overlaps are found bit by bit, so only `b` and `c` are reported
+#[overlay]
+pub struct SharedBytes {
+    #[overlay(bytes = 0..=1, bits = 0..=3)]
+    a: u16,
+    #[overlay(bytes = 0..=1, bits = 4..=9)]
+    b: u16,
+    #[overlay(byte = 0, bits = 0..=2)]
+    c: u8,
+}
+
+// little-endian, bits 8.. are in the second byte
+#[overlay]
+pub struct LittleEndian {
+    #[overlay(bytes = 0..=1, bits = 8..=11, le)]
+    a: u16,
+    #[overlay(byte = 1, bit = 3)]
+    b: bool,
+    #[overlay(byte = 0, bit = 3)]
+    c: bool,
+}
+
+#[overlay]
+pub struct PackedArray {
+    #[overlay(byte = 0, element_bits = 2)]
+    a: [u8; 3],
+    #[overlay(byte = 0, bit = 5)]
+    b: bool,
+    #[overlay(byte = 0, bit = 6)]
+    c: bool,
+}
+
+// `alias` only excuses the overlaps of the field it marks
+#[overlay]
+pub struct Aliased {
+    #[overlay(bytes = 0..=3)]
+    word: u32,
+    #[overlay(byte = 0, alias)]
+    low: u8,
+    #[overlay(byte = 3)]
+    high: u8,
+}
+
+fn main() {}
@@ -0,0 +1,48 @@
+use overlay_macro::overlay_enum;
+
+#[overlay_enum(u8)]
+pub enum Arguments {
+    A,
+}
+
+#[overlay_enum]
+pub struct NotAnEnum;
+
+#[overlay_enum]
+pub enum Discriminant {
+    A = -1,
+}
+
+#[overlay_enum]
+pub enum Overlap {
+    A = 3,
+    #[overlay_enum(range = 0..=4)]
+    B(u8),
+}
+
+#[overlay_enum]
+pub enum TwoOthers {
+    #[overlay_enum(other)]
+    A(u8),
+    #[overlay_enum(other)]
+    B(u8),
+}
+
+#[overlay_enum]
+pub enum Variants {
+    A(u8),
+    #[overlay_enum(other)]
+    B { raw: u8 },
+    #[overlay_enum(range = 4..4)]
+    C(u8),
+    #[overlay_enum(ranges = 5..=6)]
+    D(u8),
+}
+
+#[overlay_enum]
+pub enum TrailingTokens {
+    #[overlay_enum(other 1)]
+    A(u8),
+}
+
+fn main() {}
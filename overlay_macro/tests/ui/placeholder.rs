@@ -0,0 +1,22 @@
+use overlay_macro::overlay;
+
+// both fields are reported, but not the uses of the struct
+#[overlay]
+pub struct Header {
+    #[overlay(byte = 0, bits = 0..=8)]
+    a: u8,
+    #[overlay(byte = 1)]
+    b: (u8, u8),
+}
+
+#[overlay]
+pub enum Packet {
+    #[overlay(tag = 0)]
+    A(Header),
+}
+
+pub fn header(_: &Header) {}
+
+pub fn packet(_: Packet<'_>, _: PacketMut<'_>) {}
+
+fn main() {}
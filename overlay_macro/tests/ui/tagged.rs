@@ -0,0 +1,96 @@
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct Body {
+    #[overlay(byte = 0)]
+    a: u8,
+}
+
+#[overlay]
+pub enum NoTag {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0), size = 4)]
+pub enum WithSize {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0, magic = 1))]
+pub enum MagicTag {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0))]
+pub enum Variants {
+    A(Body),
+    #[overlay(tag = 1)]
+    B { body: Body },
+    #[overlay(value = 2)]
+    C,
+}
+
+#[overlay(tag(byte = 0))]
+pub struct TagOnStruct {
+    #[overlay(byte = 1)]
+    a: u8,
+}
+
+#[overlay(tag(bits = 8))]
+pub enum TagWithoutBytes {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(bytes = 0..=16))]
+pub enum TagTooWide {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0, bits = 4..=8))]
+pub enum TagBitsOutside {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0), getter_prefix = get_)]
+pub enum WithGetterPrefix {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0), warn_wide_types)]
+pub enum WithWarnWideTypes {
+    #[overlay(tag = 0)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0))]
+pub enum TrailingTokens {
+    #[overlay(tag = 0 1)]
+    A(Body),
+}
+
+#[overlay(tag(byte = 0, bits = 0..4))]
+pub enum TagValueTooWide {
+    #[overlay(tag = 15)]
+    A(Body),
+    #[overlay(tag = 0x10)]
+    B(Body),
+}
+
+#[overlay(tag(byte = 0))]
+pub enum DuplicateTags {
+    #[overlay(tag = 1)]
+    A(Body),
+    #[overlay(tag = 2)]
+    B(Body),
+    #[overlay(tag = 0x1)]
+    C,
+}
+
+fn main() {}
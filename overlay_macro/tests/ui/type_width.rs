@@ -0,0 +1,39 @@
+#![deny(deprecated)]
+
+use overlay_macro::overlay;
+
+#[overlay]
+pub struct Narrow {
+    #[overlay(bytes = 0..=1)]
+    a: u8,
+    #[overlay(bytes = 2..=4, bits = 0..20)]
+    b: u16,
+    #[overlay(bytes = 5..=8, element_bits = 9)]
+    c: [i8; 3],
+}
+
+type Half = u16;
+
+#[overlay]
+pub struct StridedAlias {
+    #[overlay(bytes = 0..=7, stride = 4, kind = int)]
+    a: [Half; 2],
+}
+
+#[overlay]
+pub struct Pointer {
+    #[overlay(bytes = 0..=8)]
+    a: usize,
+}
+
+#[overlay(warn_wide_types)]
+pub struct Wide {
+    #[overlay(byte = 0, bits = 0..=2)]
+    a: u64,
+    #[overlay(bytes = 1..=2)]
+    b: i16,
+    #[overlay(bytes = 3..=4, bits = 0..=8)]
+    c: u32,
+}
+
+fn main() {}